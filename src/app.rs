@@ -1,5 +1,4 @@
-use std::io;
-use crate::turing_machine::TuringMachine;
+use crate::turing_machine::{StepOutcome, TmError, TuringMachine};
 use crate::tui;
 
 pub struct App {
@@ -9,7 +8,7 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(conf_path: String) -> Result<Self, io::Error> {
+    pub fn new(conf_path: String) -> Result<Self, TmError> {
         let mut tm = TuringMachine::new();
         tm.load_cfg(&conf_path)?;
 
@@ -20,16 +19,18 @@ impl App {
         })
     }
 
-    pub fn run(&mut self) -> Result<(), io::Error> {
+    pub fn run(&mut self) -> Result<(), TmError> {
         let mut terminal = tui::setup_terminal()?;
 
         'outer: loop {
-            terminal.draw(|f| tui::ui(f, &&self.tm))?;
+            terminal.draw(|f| tui::ui(f, &self.tm))?;
 
             match tui::handle_events()? {
                 tui::TuiEvent::Launch => self.running = true,
                 tui::TuiEvent::Pause => self.running = false,
-                tui::TuiEvent::Step => self.tm.step(),
+                tui::TuiEvent::Step => {
+                    self.tm.step();
+                }
                 tui::TuiEvent::Restore => {
                     self.tm.reset();
                     self.tm.load_cfg(&self.conf)?
@@ -39,8 +40,8 @@ impl App {
             }
 
             if self.running {
-                self.tm.step();
-                self.running = !self.tm.is_halt();
+                self.running = self.tm.step() == StepOutcome::Applied
+                    && !self.tm.is_halt();
             }
         }
 
@@ -0,0 +1,3 @@
+pub mod app;
+pub mod tui;
+pub mod turing_machine;
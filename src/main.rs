@@ -1,10 +1,6 @@
-pub mod turing_machine;
-pub mod tui;
-pub mod app;
-
-use std::io;
 use clap::Parser;
-use crate::app::App;
+use tms::app::App;
+use tms::turing_machine::TmError;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about=None)]
@@ -17,7 +13,7 @@ struct Args {
     conf: String,
 }
 
-fn main() -> std::result::Result<(), io::Error> {
+fn main() -> std::result::Result<(), TmError> {
     let args = Args::parse();
     let conf = args.conf;
     let mut app = App::new(conf)?;
@@ -113,9 +113,9 @@ pub fn ui(frame: &mut Frame, tm: &TuringMachine) {
     let third_row = content[2];
 
     render_title(frame, first_row);
-    render_info(frame, second_row[0], &tm);
+    render_info(frame, second_row[0], tm);
     render_commands(frame, second_row[1]);
-    render_tape(frame, third_row, &tm);
+    render_tape(frame, third_row, tm);
 }
 
 //fn calculate_layout(area: Rect) -> (Rect, Vec<Vec<Rect>>) {
@@ -160,7 +160,7 @@ fn render_commands(frame: &mut Frame, area: Rect) {
     let block = Block::new()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .title(format!("( Commands )"))
+        .title("( Commands )")
         .padding(Padding::new(2, 1, 1, 1));
     
     let text = vec![
@@ -186,7 +186,7 @@ fn render_info (
     let block = Block::new()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .title(format!("( TMS Info )"))
+        .title("( TMS Info )")
         .padding(Padding::new(2, 1, 1, 1));
 
     // Span
@@ -200,9 +200,15 @@ fn render_info (
         Style::default().fg(Color::Green),
     );
 
+    let steps_span = Span::styled(
+        tm.get_step_count().to_string(),
+        Style::default().fg(Color::Green),
+    );
+
     let text = vec![
         Line::from(vec!["State:".white().bold(),  " ".into(), state_span]),
         Line::from(vec!["Cell:".white().bold(), "  ".into(), cell_span]),
+        Line::from(vec!["Steps:".white().bold(), " ".into(), steps_span]),
     ];
 
     let paragraph = Paragraph::new(text)
@@ -227,18 +233,18 @@ fn render_tape(
     let mut text = vec![];
 
 
-    for (i, c) in tape.iter().enumerate().skip(skip.into()).take(len) {
+    for (i, c) in tape.iter().enumerate().skip(skip).take(len) {
         if i == cell {
-            text.push(c.to_string().bold().on_blue().white());
+            text.push(tm.symbol_name(*c).bold().on_blue().white());
         } else {
-            text.push(c.to_string().white());
+            text.push(tm.symbol_name(*c).white());
         }
     }
 
     let block = Block::new()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .title(format!("( Tape )"))
+        .title("( Tape )")
         .padding(Padding::new(2, 1, 1, 1));
 
     let paragraph = Paragraph::new(Line::from(text))
@@ -1,37 +1,172 @@
 use std::{
+    collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     io::{self, BufRead},
-    fs::File,
+    fs::{self, File},
+    error,
     fmt,
+    ops::{Deref, DerefMut},
+    str::FromStr,
+    sync::mpsc::Sender,
+    time::{Duration, Instant},
 };
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "graphemes")]
+use unicode_segmentation::UnicodeSegmentation;
 
-#[derive(Copy, Clone, Debug)]
-enum Direction {
+mod builder;
+mod check;
+mod encoding;
+mod export;
+mod multi_tape;
+
+pub use builder::TuringMachineBuilder;
+pub use check::{Warning, WarningKind};
+pub use multi_tape::{MultiTapeInstruction, MultiTapeMachine, Tape};
+
+#[derive(Debug)]
+pub enum TmError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+    UnknownDirection { line: usize, direction: String },
+    MissingStartConfig,
+    StepLimitExceeded(usize),
+    NoTransition { state: String, symbol: String },
+    Validation(String),
+    DuplicateInstruction {
+        state: String,
+        symbol: String,
+        first_line: usize,
+        second_line: usize,
+    },
+    LoopDetected(usize),
+    HeadOutOfBounds { cell: usize, len: usize },
+    UnexpectedSymbol { symbol: String, cell: usize },
+    LeftBoundViolation { state: String, steps: usize },
+    TimedOut(usize),
+    TapeLimitExceeded(usize),
+}
+
+impl fmt::Display for TmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TmError::Io(err) => write!(f, "{err}"),
+            TmError::Parse { line, message } => {
+                write!(f, "line {line}: {message}")
+            }
+            TmError::UnknownDirection { line, direction } => write!(f,
+                "line {line}: invalid direction {direction:?}, expected left, right or stay"),
+            TmError::MissingStartConfig => {
+                write!(f, "missing start line (<state> <tape>)")
+            }
+            TmError::StepLimitExceeded(limit) => {
+                write!(f, "machine did not halt within {limit} steps")
+            }
+            TmError::NoTransition { state, symbol } => {
+                write!(f, "no transition for state {state} reading {symbol:?}")
+            }
+            TmError::Validation(message) => write!(f, "{message}"),
+            TmError::DuplicateInstruction {
+                state, symbol, first_line, second_line,
+            } => write!(f,
+                "lines {first_line} and {second_line}: duplicate instruction for state {state} reading {symbol:?}"),
+            TmError::LoopDetected(steps) => {
+                write!(f, "configuration repeated after {steps} steps")
+            }
+            TmError::HeadOutOfBounds { cell, len } => {
+                write!(f, "cell {cell} is outside the tape of length {len}")
+            }
+            TmError::UnexpectedSymbol { symbol, cell } => {
+                write!(f, "cell {cell}: no instruction reads symbol {symbol:?}")
+            }
+            TmError::LeftBoundViolation { state, steps } => write!(f,
+                "state {state} moved left of the first cell after {steps} steps"),
+            TmError::TapeLimitExceeded(limit) => {
+                write!(f, "tape would grow past {limit} cells")
+            }
+            TmError::TimedOut(steps) => {
+                write!(f, "machine did not halt in time, stopped after {steps} steps")
+            }
+        }
+    }
+}
+
+impl error::Error for TmError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            TmError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TmError {
+    fn from(err: io::Error) -> Self {
+        TmError::Io(err)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Direction {
+    #[cfg_attr(feature = "serde", serde(rename = "left"))]
     Lhs,
+    #[cfg_attr(feature = "serde", serde(rename = "right"))]
     Rhs,
+    #[cfg_attr(feature = "serde", serde(rename = "stay"))]
     Stay,
 }
 
 impl Direction {
-    fn str2dir(strdir: &str) -> Result<Direction, io::Error> {
-        match strdir {
-            "left" => Ok(Direction::Lhs),
-            "right" => Ok(Direction::Rhs),
-            "stay" => Ok(Direction::Stay),
-            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid direction")),
-        }
+    fn str2dir(strdir: &str) -> Option<Direction> {
+        strdir.parse().ok()
     }
 
     fn dir2str(dir: &Direction) -> String {
-        match dir {
-            Direction::Lhs => "left".to_string(),
-            Direction::Rhs => "right".to_string(),
-            Direction::Stay => "stay".to_string(),
+        dir.to_string()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseDirectionError(String);
+
+impl fmt::Display for ParseDirectionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid direction {:?}, expected left, right or stay", self.0)
+    }
+}
+
+impl error::Error for ParseDirectionError {}
+
+impl FromStr for Direction {
+    type Err = ParseDirectionError;
+
+    /// Besides the long forms, accepts the single letters and arrows used by
+    /// other notations, ignoring case: `l`/`<`, `r`/`>` and `s`/`-`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "left" | "l" | "<" => Ok(Direction::Lhs),
+            "right" | "r" | ">" => Ok(Direction::Rhs),
+            "stay" | "s" | "-" => Ok(Direction::Stay),
+            _ => Err(ParseDirectionError(s.to_string())),
         }
     }
 }
 
-struct Instruction {
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Direction::Lhs => "left",
+            Direction::Rhs => "right",
+            Direction::Stay => "stay",
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Instruction {
     current_state: String,
     current_symbol: char,
     new_state: String,
@@ -39,12 +174,6 @@ struct Instruction {
     direction: Direction,
 }
 
-impl Instruction {
-    fn is_matching(&self, state: &str, symbol: char) -> bool {
-        state == self.current_state && symbol == self.current_symbol
-    }
-}
-
 impl fmt::Display for Instruction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f,
@@ -57,142 +186,2727 @@ impl fmt::Display for Instruction {
     }
 }
 
-pub struct TuringMachine {
-    state: String,
-    halt_state: String,
-    tape: Vec<char>,
-    tape_cell: usize,
-    instructions: Vec<Instruction>,
+fn invalid_line(number: usize, line: &str, message: &str) -> TmError {
+    TmError::Parse {
+        line: number,
+        message: format!("{message}: {line:?}"),
+    }
 }
 
-impl TuringMachine {
-    pub fn new() -> Self {
-        Self {
-            state: String::from(""),
-            halt_state: String::from(""),
-            tape: Vec::new(),
-            tape_cell: 0,
-            instructions: Vec::new(),
+/// Splits a rule written as `q0,1 -> q1,0,R` into the five fields of the
+/// space-separated form. With several tapes the symbol and direction fields
+/// keep one comma-separated entry per tape, as in `q0,1,_ -> q1,1,1,R,R`.
+/// None if the line has no arrow.
+fn split_arrow_rule(line: &str, tapes: usize) -> Option<Result<Vec<&str>, &'static str>> {
+    let (from, to) = line.split_once("->")?;
+
+    let fields = from.split_once(',').zip(to.split_once(',')).and_then(
+        |((state, read), (new_state, rest))| {
+            let (write, moves) = rest.match_indices(',').nth(tapes - 1).map(|(i, _)| {
+                (&rest[..i], &rest[i + 1..])
+            })?;
+            Some(vec![state, read, new_state, write, moves])
+        },
+    );
+    let valid = |fields: &[&str]| {
+        fields.iter().zip([1, tapes, 1, tapes, tapes]).all(|(field, count)| {
+            let entries: Vec<&str> = field.split(',').map(str::trim).collect();
+            entries.len() == count
+                && entries.iter().all(|e| !e.is_empty() && !e.contains(char::is_whitespace))
+        })
+    };
+
+    Some(match fields {
+        Some(fields) if valid(&fields) => Ok(fields.into_iter().map(str::trim).collect()),
+        _ if tapes == 1 => {
+            Err("expected <state>,<symbol> -> <new_state>,<new_symbol>,<direction>")
         }
+        _ => Err("expected <state>,<symbols> -> <new_state>,<new_symbols>,<directions>"),
+    })
+}
+
+/// The fields of a config line: its words, or the five fields of an arrow
+/// rule.
+fn config_fields(number: usize, line: &str, tapes: usize) -> Result<Vec<&str>, TmError> {
+    match split_arrow_rule(line, tapes) {
+        Some(fields) => fields.map_err(|e| invalid_line(number, line, e)),
+        None => Ok(line.split_whitespace().collect()),
     }
+}
 
-    pub fn get_state(&self) -> &str {
-        &self.state
+/// Drops a comment: a line whose first word starts with `#` or `//`, or such a
+/// word and everything after it once the words before it make a complete rule.
+/// Anywhere else both are ordinary text, so symbols such as `#` still work and
+/// `q0 # q1 1 right` stays a rule rather than a halt line.
+fn strip_comment(line: &str, tapes: usize) -> &str {
+    let mut prev = ' ';
+    for (i, c) in line.char_indices() {
+        if prev.is_whitespace() && (c == '#' || line[i..].starts_with("//")) {
+            let before = line[..i].trim();
+            let complete_rule = match split_arrow_rule(before, tapes) {
+                Some(fields) => fields.is_ok(),
+                None => before.split_whitespace().count() == 5,
+            };
+            if before.is_empty() || complete_rule {
+                return &line[..i];
+            }
+        }
+        prev = c;
     }
+    line
+}
 
-    pub fn get_halt_state(&self) -> &str {
-        &self.halt_state
+/// Numbers config lines from 1. `lines()` already drops `\n` and `\r\n`
+/// endings; a lone `\r`, as in old Mac files, also ends a line here.
+fn numbered_lines<'a, I>(lines: I) -> impl Iterator<Item = (usize, &'a str)>
+where
+    I: Iterator<Item = &'a str>,
+{
+    lines.flat_map(|line| line.split('\r')).enumerate().map(|(i, line)| (i + 1, line))
+}
+
+/// A machine `run_with` can drive.
+trait Stepper {
+    fn step(&mut self) -> StepOutcome;
+    fn is_halt(&self) -> bool;
+    /// The error a run reports when `step` could not apply a transition.
+    fn stop_error(&self, outcome: StepOutcome) -> TmError;
+}
+
+/// The step loop behind every run: advances `tm` through `step` until it
+/// halts, gets stuck or has applied `max_steps` transitions. `step` returns
+/// None to stop the run early.
+fn run_with<M, F>(tm: &mut M, max_steps: usize, mut step: F) -> RunResult
+where
+    M: Stepper,
+    F: FnMut(&mut M) -> Option<StepOutcome>,
+{
+    let mut steps = 0;
+
+    loop {
+        if tm.is_halt() {
+            return RunResult::Halted { steps };
+        }
+        if steps == max_steps {
+            return RunResult::StepLimitExceeded { steps };
+        }
+
+        match step(tm) {
+            None => return RunResult::Stopped { steps },
+            Some(StepOutcome::Applied) => steps += 1,
+            Some(StepOutcome::Halted) => return RunResult::Halted { steps },
+            Some(StepOutcome::NoTransition) => return RunResult::NoTransition { steps },
+            Some(StepOutcome::LeftBoundViolation) => {
+                return RunResult::LeftBoundViolation { steps };
+            }
+            Some(StepOutcome::TapeLimitExceeded) => {
+                return RunResult::TapeLimitExceeded { steps };
+            }
+        }
     }
+}
 
-    pub fn get_tape(&self) -> &[char] {
-        &self.tape
+/// `run` for any machine: steps until it halts, failing if it gets stuck or
+/// runs out of steps.
+fn run_to_halt<M: Stepper>(tm: &mut M, max_steps: usize) -> Result<usize, TmError> {
+    let result = run_with(tm, max_steps, |tm| Some(tm.step()));
+    finish(tm, result)
+}
+
+/// Turns the result of `run_with` into the steps or the error `run` returns.
+fn finish<M: Stepper>(tm: &M, result: RunResult) -> Result<usize, TmError> {
+    match result {
+        RunResult::Halted { steps }
+        | RunResult::Stopped { steps }
+        | RunResult::Breakpoint { steps, .. }
+        | RunResult::Watchpoint { steps, .. } => Ok(steps),
+        RunResult::StepLimitExceeded { steps } => Err(TmError::StepLimitExceeded(steps)),
+        RunResult::NoTransition { .. } => Err(tm.stop_error(StepOutcome::NoTransition)),
+        RunResult::LeftBoundViolation { .. } => {
+            Err(tm.stop_error(StepOutcome::LeftBoundViolation))
+        }
+        RunResult::TapeLimitExceeded { .. } => {
+            Err(tm.stop_error(StepOutcome::TapeLimitExceeded))
+        }
     }
+}
 
-    pub fn get_tape_cell(&self) -> usize {
-        self.tape_cell
+/// Separates the machines of a file read by `load_all`.
+const MACHINE_SEPARATOR: &str = "---";
+
+/// Marks the initial head position inside a start line tape, e.g. `00@110`.
+const HEAD_MARKER: char = '@';
+
+/// Splits a start line tape into its cells: one per character, or with the
+/// `graphemes` feature one per grapheme cluster, so that an `é` written as `e`
+/// and a combining accent is a single symbol.
+#[cfg(not(feature = "graphemes"))]
+fn tape_cells(tape: &str) -> Vec<&str> {
+    tape.char_indices()
+        .map(|(i, c)| &tape[i..i + c.len_utf8()])
+        .collect()
+}
+
+#[cfg(feature = "graphemes")]
+fn tape_cells(tape: &str) -> Vec<&str> {
+    tape.graphemes(true).collect()
+}
+
+/// Strips the head marker from a start line tape, returning the cells and the
+/// index of the cell right after the marker (0 if there is none).
+fn split_head_marker(tape: &str) -> Result<(Vec<&str>, usize), String> {
+    match tape.matches(HEAD_MARKER).count() {
+        0 => Ok((tape_cells(tape), 0)),
+        1 => {
+            let (before, after) = tape.split_once(HEAD_MARKER).unwrap();
+            let before = tape_cells(before);
+            let cell = before.len();
+            Ok(([before, tape_cells(after)].concat(), cell))
+        }
+        n => Err(format!("expected at most one {HEAD_MARKER:?} head marker, found {n}")),
     }
+}
 
-    pub fn load_cfg(&mut self, path: &str) -> Result<(), io::Error> {
-        let file = File::open(path).unwrap(); 
-        let reader = io::BufReader::new(file);
+/// Multi-character symbols are interned as characters from the Unicode
+/// private use area, so the tape and instructions can keep using `char`.
+/// Configs may not use these characters themselves.
+const SYMBOL_IDS: std::ops::RangeInclusive<u32> = 0xE000..=0xF8FF;
 
-        for line in reader.lines() {
-            let line = line?;
-            if line.starts_with('#') || line.trim().is_empty() {
-                continue;
-            }
+/// Words that open a directive line. They cannot name a state, otherwise a
+/// start line for a state called `blank` would read as a `blank` directive.
+const RESERVED_WORDS: &[&str] = &[
+    "start", "tape", "blank", "accept", "reject",
+    "name", "machine", "head", "states", "strict", "tape_count", "nondeterministic",
+];
 
-            let substrings: Vec<&str> = line.split_whitespace().collect();
-            let count = substrings.len();
+/// Why `state` cannot name a state, if it cannot.
+fn state_name_error(state: &str) -> Option<String> {
+    if RESERVED_WORDS.contains(&state) {
+        return Some(format!("{state} is a reserved word and cannot name a state"));
+    }
+    // A saved config starts lines with state names, which must not read as
+    // comments.
+    if state.starts_with('#') || state.starts_with("//") {
+        return Some(format!("{state} starts like a comment and cannot name a state"));
+    }
+    None
+}
 
-            match count {
-                2 => {
-                    self.state = substrings[0].to_string();
-                    self.tape = substrings[1].chars().collect();
-                }
-                1 => {
-                    self.halt_state = substrings[0].to_string();
-                }
-                5 => {
-                    let current_state = substrings[0].to_string();
-                    let current_symbol = substrings[1].chars().next().unwrap();
-                    let new_state = substrings[2].to_string();
-                    let new_symbol = substrings[3].chars().next().unwrap();
-                    let direction = Direction::str2dir(substrings[4])
-                        .unwrap();
+fn check_state_name(number: usize, line: &str, state: &str) -> Result<(), TmError> {
+    match state_name_error(state) {
+        Some(err) => Err(invalid_line(number, line, &err)),
+        None => Ok(()),
+    }
+}
 
-                    self.instructions.push(Instruction {
-                        current_state,
-                        current_symbol,
-                        new_state,
-                        new_symbol,
-                        direction,
-                    });
-                }
-                _ => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        "Invalid line in CFG file.",
-                    ));
-                }
-            }
+/// What a machine does with the config lines every kind of machine shares;
+/// see `apply_shared_directive`.
+trait ConfigTarget {
+    fn set_blank(&mut self, name: &str) -> Result<(), String>;
+    fn set_name(&mut self, name: &str);
+    fn add_halt(&mut self, state: &str);
+    fn add_accept(&mut self, state: &str);
+    fn add_reject(&mut self, state: &str);
+}
+
+/// Applies a halt state line or a `blank`, `name`, `machine`, `accept` or
+/// `reject` directive. Returns false for any other line, which is left to the
+/// machine's own parser.
+fn apply_shared_directive<T: ConfigTarget>(
+    target: &mut T,
+    number: usize,
+    line: &str,
+    fields: &[&str],
+) -> Result<bool, TmError> {
+    match *fields {
+        ["blank", name] => {
+            target.set_blank(name).map_err(|e| invalid_line(number, line, &e))?;
+        }
+        ["name" | "machine", name] => target.set_name(name),
+        ["accept", state] => {
+            check_state_name(number, line, state)?;
+            target.add_accept(state);
         }
+        ["reject", state] => {
+            check_state_name(number, line, state)?;
+            target.add_reject(state);
+        }
+        [state] if !RESERVED_WORDS.contains(&state) => target.add_halt(state),
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
 
-        Ok(())
+/// Records the (state, symbol) key of rule `number`, failing if an earlier
+/// rule has the same key. Symbols are compared by name.
+fn check_duplicate(
+    seen: &mut HashMap<(String, String), usize>,
+    state: &str,
+    symbol: &str,
+    number: usize,
+) -> Result<(), TmError> {
+    match seen.entry((state.to_string(), symbol.to_string())) {
+        Entry::Occupied(first) => Err(TmError::DuplicateInstruction {
+            state: state.to_string(),
+            symbol: symbol.to_string(),
+            first_line: *first.get(),
+            second_line: number,
+        }),
+        Entry::Vacant(entry) => {
+            entry.insert(number);
+            Ok(())
+        }
     }
+}
 
-    pub fn step(&mut self) {
-        for i in 0..self.instructions.len() {
-            if self.instructions[i]
-                .is_matching(&self.state, self.tape[self.tape_cell]) {
-                let state: String = self.instructions[i].new_state.clone();
-                let symbol: char = self.instructions[i].new_symbol;
-                let direction: Direction = self.instructions[i].direction;
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StepOutcome {
+    Applied,
+    Halted,
+    NoTransition,
+    /// The transition would move the head left of cell 0 of a left-bounded
+    /// tape; the machine is left untouched.
+    LeftBoundViolation,
+    /// The transition would grow the tape past `max_tape_size`; the machine
+    /// is left untouched.
+    TapeLimitExceeded,
+}
 
-                self.update(&state, symbol, direction); 
-                break;
+/// One applied transition of `run_trace`. `step` is the step count after the
+/// transition and `head` the offset the symbol was read at.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    pub step: usize,
+    pub state: String,
+    pub head: isize,
+    pub read: String,
+    pub written: String,
+    pub direction: Direction,
+    pub new_state: String,
+}
+
+/// How two machines compare on the same input in `run_equivalent`. A machine
+/// that gets stuck counts as halting without an outcome.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Equivalence {
+    Equivalent,
+    DifferentTape,
+    DifferentOutcome,
+    OnlyFirstHalts,
+    OnlySecondHalts,
+    NeitherHalts,
+}
+
+/// Why `run_until` or `run_until_breakpoint` stopped, with the number of
+/// steps applied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RunResult {
+    Halted { steps: usize },
+    /// The stop condition of `run_until` held.
+    Stopped { steps: usize },
+    /// `symbol` is set when a breakpoint on a (state, symbol) pair was hit.
+    Breakpoint { state: String, symbol: Option<char>, steps: usize },
+    /// A transition overwrote a watched cell, given as an input offset.
+    Watchpoint { cell: isize, old: char, new: char, steps: usize },
+    NoTransition { steps: usize },
+    LeftBoundViolation { steps: usize },
+    TapeLimitExceeded { steps: usize },
+    StepLimitExceeded { steps: usize },
+}
+
+/// An applied transition as seen by `run_with_observer` and `step_observed`:
+/// the instruction that fired, the head offset before it moved and the state
+/// the machine is in now.
+pub struct StepEvent<'a> {
+    pub instruction: &'a Instruction,
+    pub head_before: isize,
+    pub state: &'a str,
+}
+
+/// In the current symbol column `*` matches any symbol; in the new symbol
+/// column it leaves the cell unchanged.
+const WILDCARD: char = '*';
+
+impl Instruction {
+    pub fn current_state(&self) -> &str {
+        &self.current_state
+    }
+
+    /// A multi-character symbol comes back as the private use character it
+    /// was interned as; `current_symbol_name` gives its name.
+    pub fn current_symbol(&self) -> char {
+        self.current_symbol
+    }
+
+    /// The name of the read symbol, looked up in the machine that owns this
+    /// instruction.
+    pub fn current_symbol_name(&self, tm: &TuringMachine) -> String {
+        tm.symbol_name(self.current_symbol)
+    }
+
+    pub fn new_state(&self) -> &str {
+        &self.new_state
+    }
+
+    /// Like `current_symbol`, a private use character for multi-character
+    /// symbols; see `new_symbol_name`.
+    pub fn new_symbol(&self) -> char {
+        self.new_symbol
+    }
+
+    pub fn new_symbol_name(&self, tm: &TuringMachine) -> String {
+        tm.symbol_name(self.new_symbol)
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn written(&self, symbol: char) -> char {
+        if self.new_symbol == WILDCARD { symbol } else { self.new_symbol }
+    }
+}
+
+/// The instructions in config order, plus the first instruction for each
+/// (state, symbol) pair keyed by state so `step` can look it up without
+/// allocating.
+#[derive(Clone, Default)]
+struct InstructionSet {
+    instructions: Vec<Instruction>,
+    index: HashMap<String, HashMap<char, usize>>,
+}
+
+impl InstructionSet {
+    fn push(&mut self, instruction: Instruction) {
+        self.index
+            .entry(instruction.current_state.clone())
+            .or_default()
+            .entry(instruction.current_symbol)
+            .or_insert(self.instructions.len());
+
+        self.instructions.push(instruction);
+    }
+
+    fn find(&self, state: &str, symbol: char) -> Option<usize> {
+        self.index
+            .get(state)
+            .and_then(|rules| rules.get(&symbol).or_else(|| rules.get(&WILDCARD)))
+            .copied()
+    }
+
+    /// Every instruction that applies to (state, symbol), in config order.
+    /// Wildcard rules only apply when there is no exact rule for the symbol.
+    fn matching<'a: 's, 's>(
+        &'a self,
+        state: &'s str,
+        symbol: char,
+    ) -> impl Iterator<Item = &'a Instruction> + 's {
+        let exact = self.index
+            .get(state)
+            .is_some_and(|rules| rules.contains_key(&symbol));
+        let key = if exact { symbol } else { WILDCARD };
+
+        self.instructions
+            .iter()
+            .filter(move |i| i.current_state == state && i.current_symbol == key)
+    }
+
+    /// Swaps in `instruction` for the first rule with the same state and read
+    /// symbol, or adds it if there is none. The key stays the same, so the
+    /// index does not change.
+    fn replace(&mut self, instruction: Instruction) -> bool {
+        let existing = self.index
+            .get(&instruction.current_state)
+            .and_then(|rules| rules.get(&instruction.current_symbol))
+            .copied();
+
+        match existing {
+            Some(i) => {
+                self.instructions[i] = instruction;
+                true
+            }
+            None => {
+                self.push(instruction);
+                false
             }
         }
     }
 
-    pub fn is_halt(&self) -> bool {
-        self.state == self.halt_state
+    /// Drops every rule for (state, symbol). Later rules move up, so the index
+    /// is rebuilt.
+    fn remove(&mut self, state: &str, symbol: char) -> bool {
+        let len = self.instructions.len();
+        let mut instructions = std::mem::take(&mut self.instructions);
+        instructions.retain(|i| i.current_state != state || i.current_symbol != symbol);
+
+        let removed = instructions.len() != len;
+        *self = instructions.into();
+        removed
+    }
+
+    fn has_state(&self, state: &str) -> bool {
+        self.index.contains_key(state)
     }
 
+    fn clear(&mut self) {
+        self.instructions.clear();
+        self.index.clear();
+    }
+}
+
+impl Deref for InstructionSet {
+    type Target = [Instruction];
 
-    /* TEMPORANY METHOD (Test purpose only)
-    pub fn print_tape(&self) {
-        println!("{:?}", self.tape);
+    fn deref(&self) -> &[Instruction] {
+        &self.instructions
     }
+}
 
-    pub fn print_info(&self) {
-        println!("State: {}", self.state);
-        println!("Halt state: {}", self.halt_state);
-        println!("Tape: {:?}", self.tape);
-        println!("Tape cell: {}", self.tape_cell);
-        println!("Instructions:");
-        for (i, instruction) in self.instructions.iter().enumerate() {
-            println!("#{}: {}", i + 1, instruction);
+impl From<Vec<Instruction>> for InstructionSet {
+    fn from(instructions: Vec<Instruction>) -> Self {
+        let mut set = Self::default();
+        for instruction in instructions {
+            set.push(instruction);
         }
+        set
     }
-    */
+}
 
-    fn update(&mut self, new_state: &str, new_symbol: char, dir: Direction) {
-        self.state = new_state.to_string();
-        self.tape[self.tape_cell] = new_symbol;
+impl From<InstructionSet> for Vec<Instruction> {
+    fn from(set: InstructionSet) -> Self {
+        set.instructions
+    }
+}
+
+/// How the tape behaves at its ends. An infinite tape grows with blank cells,
+/// a circular one keeps its length and wraps the head around, and a
+/// left-bounded one only grows to the right.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TapeMode {
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "infinite"))]
+    Infinite,
+    #[cfg_attr(feature = "serde", serde(rename = "circular"))]
+    Circular,
+    #[cfg_attr(feature = "serde", serde(rename = "left-bounded"))]
+    LeftBounded,
+}
+
+/// Room for new cells left of the tape when it first grows leftwards.
+const LEFT_ROOM: usize = 16;
 
+/// Tape cells with spare room kept on their left, so that growing the tape
+/// leftwards is amortized O(1) just like growing it rightwards. The room is
+/// doubled whenever it runs out.
+#[derive(Clone, Default)]
+struct Cells {
+    buf: Vec<char>,
+    start: usize,
+}
+
+impl Cells {
+    fn push_front(&mut self, symbol: char) {
+        if self.start == 0 {
+            let room = self.len().max(LEFT_ROOM);
+            self.buf.splice(0..0, std::iter::repeat_n(symbol, room));
+            self.start = room;
+        }
+
+        self.start -= 1;
+        self.buf[self.start] = symbol;
+    }
+
+    fn pop_front(&mut self) -> Option<char> {
+        let symbol = self.first().copied()?;
+        self.start += 1;
+        Some(symbol)
+    }
+
+    fn push(&mut self, symbol: char) {
+        self.buf.push(symbol);
+    }
+
+    fn pop(&mut self) -> Option<char> {
+        if self.is_empty() { None } else { self.buf.pop() }
+    }
+
+    fn resize(&mut self, len: usize, symbol: char) {
+        self.buf.resize(self.start + len, symbol);
+    }
+}
+
+impl Deref for Cells {
+    type Target = [char];
+
+    fn deref(&self) -> &[char] {
+        &self.buf[self.start..]
+    }
+}
+
+impl DerefMut for Cells {
+    fn deref_mut(&mut self) -> &mut [char] {
+        &mut self.buf[self.start..]
+    }
+}
+
+impl PartialEq for Cells {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for Cells {}
+
+impl fmt::Debug for Cells {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl From<Vec<char>> for Cells {
+    fn from(buf: Vec<char>) -> Self {
+        Self { buf, start: 0 }
+    }
+}
+
+impl From<Cells> for Vec<char> {
+    fn from(cells: Cells) -> Self {
+        cells.to_vec()
+    }
+}
+
+impl FromIterator<char> for Cells {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        Vec::from_iter(iter).into()
+    }
+}
+
+/// Moves the head one cell, materializing a blank when it walks off either
+/// end of an infinite tape. `origin` tracks where cell 0 of the input ended up.
+/// The head stays put when it would leave a left-bounded tape.
+fn shift(
+    tape: &mut Cells,
+    cell: &mut usize,
+    origin: &mut usize,
+    blank: char,
+    dir: Direction,
+    mode: TapeMode,
+) {
+    if mode == TapeMode::Circular {
+        if tape.is_empty() {
+            tape.push(blank);
+        }
+        let len = tape.len();
         match dir {
-            Direction::Lhs => self.tape_cell -= 1,
-            Direction::Rhs => self.tape_cell += 1,
+            Direction::Lhs => *cell = (*cell + len - 1) % len,
+            Direction::Rhs => *cell = (*cell + 1) % len,
             Direction::Stay => {},
         }
+        return;
     }
 
-    pub fn reset(&mut self) {
-        self.state.clear();
-        self.halt_state.clear();
-        self.tape.clear();
-        self.tape_cell = 0;
-        self.instructions.clear();
+    match dir {
+        Direction::Lhs => {
+            if *cell == 0 && mode == TapeMode::LeftBounded {
+                return;
+            }
+            if *cell == 0 {
+                tape.push_front(blank);
+                *origin += 1;
+            } else {
+                *cell -= 1;
+            }
+        }
+        Direction::Rhs => {
+            *cell += 1;
+            if *cell >= tape.len() {
+                tape.resize(*cell + 1, blank);
+            }
+        }
+        Direction::Stay => {},
+    }
+}
+
+/// The execution state of a machine without its instructions, as taken by
+/// `snapshot` and put back by `restore`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Configuration {
+    pub state: String,
+    pub tape: Vec<char>,
+    pub tape_cell: usize,
+    pub origin: usize,
+}
+
+/// What a single applied transition overwrote, so it can be undone.
+#[derive(Clone)]
+struct HistoryEntry {
+    state: String,
+    head: isize,
+    symbol: char,
+    extended: bool,
+    leftmost: isize,
+    rightmost: isize,
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Steps between clock reads in `run_for`.
+const TIME_CHECK_INTERVAL: usize = 1024;
+
+const DEFAULT_BLANK: char = '_';
+
+/// Number of tape cells shown around the head by `Display`.
+const DISPLAY_WINDOW: usize = 32;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Accept,
+    Reject,
+}
+
+/// With the `serde` feature a machine serializes as its current
+/// configuration and rules, with symbols written out by name; see
+/// `MachineData`. It deserializes into a machine that starts from there.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "MachineData", into = "MachineData"),
+)]
+pub struct TuringMachine {
+    state: String,
+    halt_states: BTreeSet<String>,
+    accept_states: BTreeSet<String>,
+    reject_states: BTreeSet<String>,
+    tape: Cells,
+    tape_cell: usize,
+    origin: usize,
+    blank: char,
+    tape_mode: TapeMode,
+    max_tape_size: Option<usize>,
+    symbol_names: Vec<String>,
+    initial_state: String,
+    initial_tape: Vec<char>,
+    initial_tape_cell: usize,
+    steps: usize,
+    visits: BTreeMap<String, usize>,
+    leftmost: isize,
+    rightmost: isize,
+    history: VecDeque<HistoryEntry>,
+    history_capacity: usize,
+    nondeterministic: bool,
+    ignore_state_case: bool,
+    name: Option<String>,
+    declared_states: Option<BTreeSet<String>>,
+    breakpoints: HashSet<String>,
+    symbol_breakpoints: HashSet<(String, char)>,
+    watched_cells: HashSet<isize>,
+    instructions: InstructionSet,
+}
+
+/// The serialized form of a `TuringMachine`. Only `state`, `halt_states`,
+/// `tape` and `instructions` are required, so a machine can be written by
+/// hand as JSON; `head` is an index into `tape` and `origin` the index of
+/// input cell 0.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct MachineData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    state: String,
+    halt_states: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    accept_states: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    reject_states: BTreeSet<String>,
+    #[serde(default = "default_blank")]
+    blank: String,
+    tape: Vec<String>,
+    #[serde(default)]
+    head: usize,
+    #[serde(default)]
+    origin: usize,
+    #[serde(default)]
+    tape_mode: TapeMode,
+    #[serde(default)]
+    nondeterministic: bool,
+    instructions: Vec<RuleData>,
+}
+
+#[cfg(feature = "serde")]
+fn default_blank() -> String {
+    DEFAULT_BLANK.to_string()
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct RuleData {
+    current_state: String,
+    current_symbol: String,
+    new_state: String,
+    new_symbol: String,
+    direction: Direction,
+}
+
+#[cfg(feature = "serde")]
+impl From<TuringMachine> for MachineData {
+    fn from(tm: TuringMachine) -> Self {
+        let instructions = tm.instructions
+            .iter()
+            .map(|i| RuleData {
+                current_state: i.current_state.clone(),
+                current_symbol: tm.symbol_name(i.current_symbol),
+                new_state: i.new_state.clone(),
+                new_symbol: tm.symbol_name(i.new_symbol),
+                direction: i.direction,
+            })
+            .collect();
+
+        Self {
+            name: tm.name.clone(),
+            tape: tm.tape.iter().map(|&c| tm.symbol_name(c)).collect(),
+            blank: tm.symbol_name(tm.blank),
+            head: tm.tape_cell,
+            origin: tm.origin,
+            tape_mode: tm.tape_mode,
+            nondeterministic: tm.nondeterministic,
+            instructions,
+            state: tm.state,
+            halt_states: tm.halt_states,
+            accept_states: tm.accept_states,
+            reject_states: tm.reject_states,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<MachineData> for TuringMachine {
+    type Error = TmError;
+
+    fn try_from(data: MachineData) -> Result<Self, TmError> {
+        let mut tm = TuringMachine::new();
+
+        tm.blank = tm.intern_blank(&data.blank).map_err(TmError::Validation)?;
+        tm.tape = data.tape
+            .iter()
+            .map(|name| tm.intern(name))
+            .collect::<Result<_, _>>()
+            .map_err(TmError::Validation)?;
+        if data.head > tm.tape.len() {
+            return Err(TmError::HeadOutOfBounds { cell: data.head, len: tm.tape.len() });
+        }
+        if data.origin > tm.tape.len() {
+            return Err(TmError::Validation(format!(
+                "origin {} is outside the tape of length {}",
+                data.origin,
+                tm.tape.len(),
+            )));
+        }
+
+        for rule in data.instructions {
+            let instruction = Instruction {
+                current_symbol: tm.intern(&rule.current_symbol).map_err(TmError::Validation)?,
+                new_symbol: tm.intern(&rule.new_symbol).map_err(TmError::Validation)?,
+                current_state: rule.current_state,
+                new_state: rule.new_state,
+                direction: rule.direction,
+            };
+            tm.instructions.push(instruction);
+        }
+
+        tm.name = data.name;
+        tm.state = data.state;
+        tm.halt_states = data.halt_states;
+        tm.halt_states.extend(data.accept_states.iter().chain(&data.reject_states).cloned());
+        tm.accept_states = data.accept_states;
+        tm.reject_states = data.reject_states;
+        tm.tape_cell = data.head;
+        tm.origin = data.origin;
+        tm.tape_mode = data.tape_mode;
+        tm.nondeterministic = data.nondeterministic;
+        tm.save_initial();
+
+        Ok(tm)
+    }
+}
+
+impl ConfigTarget for TuringMachine {
+    fn set_blank(&mut self, name: &str) -> Result<(), String> {
+        self.blank = self.intern_blank(name)?;
+        Ok(())
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+
+    fn add_halt(&mut self, state: &str) {
+        self.add_halt_state(state);
+    }
+
+    fn add_accept(&mut self, state: &str) {
+        self.add_accept_state(state);
+    }
+
+    fn add_reject(&mut self, state: &str) {
+        self.add_reject_state(state);
+    }
+}
+
+impl Stepper for TuringMachine {
+    fn step(&mut self) -> StepOutcome {
+        TuringMachine::step(self)
+    }
+
+    fn is_halt(&self) -> bool {
+        TuringMachine::is_halt(self)
+    }
+
+    fn stop_error(&self, outcome: StepOutcome) -> TmError {
+        match outcome {
+            StepOutcome::LeftBoundViolation => TmError::LeftBoundViolation {
+                state: self.state.clone(),
+                steps: self.steps,
+            },
+            StepOutcome::TapeLimitExceeded => {
+                TmError::TapeLimitExceeded(self.max_tape_size.unwrap_or(0))
+            }
+            _ => TmError::NoTransition {
+                state: self.state.clone(),
+                symbol: self.symbol_name(self.head_symbol()),
+            },
+        }
+    }
+}
+
+impl Default for TuringMachine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TuringMachine {
+    pub fn new() -> Self {
+        Self {
+            state: String::from(""),
+            halt_states: BTreeSet::new(),
+            accept_states: BTreeSet::new(),
+            reject_states: BTreeSet::new(),
+            tape: Cells::default(),
+            tape_cell: 0,
+            origin: 0,
+            blank: DEFAULT_BLANK,
+            tape_mode: TapeMode::Infinite,
+            max_tape_size: None,
+            symbol_names: Vec::new(),
+            initial_state: String::new(),
+            initial_tape: Vec::new(),
+            initial_tape_cell: 0,
+            steps: 0,
+            visits: BTreeMap::new(),
+            leftmost: 0,
+            rightmost: 0,
+            history: VecDeque::new(),
+            history_capacity: 0,
+            nondeterministic: false,
+            ignore_state_case: false,
+            name: None,
+            declared_states: None,
+            breakpoints: HashSet::new(),
+            symbol_breakpoints: HashSet::new(),
+            watched_cells: HashSet::new(),
+            instructions: InstructionSet::default(),
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn get_state(&self) -> &str {
+        &self.state
+    }
+
+    /// The first halt state in sorted order, or `""` if there is none.
+    #[deprecated(note = "machines may have several halt states; use `get_halt_states`")]
+    pub fn get_halt_state(&self) -> &str {
+        self.get_halt_states().next().unwrap_or("")
+    }
+
+    pub fn get_halt_states(&self) -> impl Iterator<Item = &str> {
+        self.halt_states.iter().map(String::as_str)
+    }
+
+    pub fn get_tape(&self) -> &[char] {
+        &self.tape
+    }
+
+    pub fn get_tape_cell(&self) -> usize {
+        self.tape_cell
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// The symbol at a head offset, whether or not that cell is materialized.
+    fn cell_at(&self, offset: isize) -> char {
+        usize::try_from(self.origin as isize + offset)
+            .ok()
+            .and_then(|i| self.tape.get(i))
+            .copied()
+            .unwrap_or(self.blank)
+    }
+
+    /// `len` cells starting at offset `start` from input cell 0, as
+    /// `get_head_offset` counts them. Cells the head never reached are blank.
+    pub fn tape_slice(&self, start: isize, len: usize) -> Vec<char> {
+        (0..len as isize).map(|i| self.cell_at(start + i)).collect()
+    }
+
+    /// Symbols read or written by any rule, plus the blank. The `*` wildcard is
+    /// not a symbol and is left out.
+    pub fn alphabet(&self) -> BTreeSet<char> {
+        self.instructions
+            .iter()
+            .flat_map(|i| [i.current_symbol, i.new_symbol])
+            .filter(|&c| c != WILDCARD)
+            .chain([self.blank])
+            .collect()
+    }
+
+    /// The start state, the halt states and every state named by a rule.
+    pub fn states(&self) -> BTreeSet<String> {
+        let mut states: BTreeSet<String> = self.instructions
+            .iter()
+            .flat_map(|i| [i.current_state.clone(), i.new_state.clone()])
+            .collect();
+        states.extend(self.halt_states.iter().cloned());
+        if !self.initial_state.is_empty() {
+            states.insert(self.initial_state.clone());
+        }
+        states
+    }
+
+    /// Rewrites every symbol of the rules, the tape and the blank through
+    /// `mapping`; symbols it does not mention stay as they are. Fails without
+    /// changing anything if two distinct symbols would end up the same, which
+    /// includes mapping one onto the blank while the blank stays, or if one
+    /// would become the `*` wildcard.
+    pub fn remap_symbols(&mut self, mapping: &HashMap<char, char>) -> Result<(), TmError> {
+        let map = |c: char| match c {
+            WILDCARD => WILDCARD,
+            c => mapping.get(&c).copied().unwrap_or(c),
+        };
+
+        if let Some((&symbol, _)) = mapping.iter().find(|&(_, &image)| image == WILDCARD) {
+            return Err(TmError::Validation(format!(
+                "symbol {:?} cannot map to the {WILDCARD:?} wildcard",
+                self.symbol_name(symbol),
+            )));
+        }
+
+        let mut symbols = self.alphabet();
+        symbols.extend(self.tape.iter().chain(&self.initial_tape).copied());
+        symbols.extend(mapping.keys().copied());
+
+        let mut images: HashMap<char, char> = HashMap::new();
+        for symbol in symbols {
+            if let Some(other) = images.insert(map(symbol), symbol) {
+                return Err(TmError::Validation(format!(
+                    "symbols {:?} and {:?} would both map to {:?}",
+                    self.symbol_name(other),
+                    self.symbol_name(symbol),
+                    self.symbol_name(map(symbol)),
+                )));
+            }
+        }
+
+        let instructions: Vec<Instruction> = std::mem::take(&mut self.instructions).into();
+        self.instructions = instructions
+            .into_iter()
+            .map(|i| Instruction {
+                current_symbol: map(i.current_symbol),
+                new_symbol: map(i.new_symbol),
+                ..i
+            })
+            .collect::<Vec<_>>()
+            .into();
+
+        for c in self.tape.iter_mut().chain(&mut self.initial_tape) {
+            *c = map(*c);
+        }
+        for entry in self.history.iter_mut() {
+            entry.symbol = map(entry.symbol);
+        }
+        self.symbol_breakpoints = self.symbol_breakpoints
+            .drain()
+            .map(|(state, symbol)| (state, map(symbol)))
+            .collect();
+        self.blank = map(self.blank);
+
+        Ok(())
+    }
+
+    pub fn get_blank(&self) -> char {
+        self.blank
+    }
+
+    /// The character standing for a symbol name. Single characters stand for
+    /// themselves; longer names are only known once a config has used them.
+    pub fn symbol(&self, name: &str) -> Option<char> {
+        let mut chars = name.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(symbol), None) => Some(symbol),
+            _ => self.symbol_names
+                .iter()
+                .position(|n| n == name)
+                .and_then(|i| char::from_u32(SYMBOL_IDS.start() + i as u32)),
+        }
+    }
+
+    pub fn symbol_name(&self, symbol: char) -> String {
+        (symbol as u32)
+            .checked_sub(*SYMBOL_IDS.start())
+            .and_then(|i| self.symbol_names.get(i as usize))
+            .cloned()
+            .unwrap_or_else(|| symbol.to_string())
+    }
+
+    fn intern_cells(&mut self, names: Vec<&str>) -> Result<Cells, String> {
+        names.into_iter().map(|name| self.intern(name)).collect()
+    }
+
+    /// Splits a tape into interned cells and the index of the head cell,
+    /// either as written on a start line or, when it has several words, as on
+    /// a `tape` line.
+    fn parse_tape(&mut self, tape: &str) -> Result<(Cells, usize), String> {
+        let words: Vec<&str> = tape.split_whitespace().collect();
+        let (cells, cell) = if words.len() > 1 {
+            (words, 0)
+        } else {
+            split_head_marker(tape.trim())?
+        };
+
+        Ok((self.intern_cells(cells)?, cell))
+    }
+
+    fn intern(&mut self, name: &str) -> Result<char, String> {
+        if let Some(c) = name.chars().find(|&c| SYMBOL_IDS.contains(&(c as u32))) {
+            return Err(format!("private use character {c:?} cannot be part of a symbol"));
+        }
+        if let Some(symbol) = self.symbol(name) {
+            return Ok(symbol);
+        }
+
+        let id = SYMBOL_IDS.start() + self.symbol_names.len() as u32;
+        if !SYMBOL_IDS.contains(&id) {
+            return Err(format!("too many multi-character symbols at {name:?}"));
+        }
+
+        self.symbol_names.push(name.to_string());
+        Ok(char::from_u32(id).unwrap())
+    }
+
+    /// The blank fills new cells, so it must be a single tape cell.
+    fn intern_blank(&mut self, name: &str) -> Result<char, String> {
+        if tape_cells(name).len() != 1 {
+            return Err(format!("the blank must be a single tape cell, found {name:?}"));
+        }
+        self.intern(name)
+    }
+
+    pub fn get_step_count(&self) -> usize {
+        self.steps
+    }
+
+    /// Head position relative to the first cell of the loaded input, which
+    /// stays put while blanks are prepended on the left.
+    pub fn get_head_offset(&self) -> isize {
+        self.tape_cell as isize - self.origin as isize
+    }
+
+    /// `tape` is read like the tape of a config: one cell per character, or
+    /// per grapheme cluster with the `graphemes` feature, with an optional
+    /// `@` head marker, or, if it holds several words, one symbol per word
+    /// like a `tape` line. Fails on a state a config could not name.
+    pub fn set_start(&mut self, state: &str, tape: &str) -> Result<(), TmError> {
+        if let Some(err) = state_name_error(state) {
+            return Err(TmError::Validation(err));
+        }
+
+        let (tape, cell) = self.parse_tape(tape).map_err(TmError::Validation)?;
+
+        self.state = state.to_string();
+        self.tape = tape;
+        self.tape_cell = cell;
+        self.origin = 0;
+        self.save_initial();
+        Ok(())
+    }
+
+    /// A circular tape keeps the length it has when the machine starts moving.
+    pub fn set_tape_mode(&mut self, mode: TapeMode) {
+        self.tape_mode = mode;
+    }
+
+    pub fn get_tape_mode(&self) -> TapeMode {
+        self.tape_mode
+    }
+
+    /// Makes `step` refuse transitions that would grow the tape past `max`
+    /// cells. None, the default, leaves the tape unbounded.
+    pub fn set_max_tape_size(&mut self, max: Option<usize>) {
+        self.max_tape_size = max;
+    }
+
+    /// Allows several instructions for the same (state, symbol) pair when
+    /// loading a config, as a `nondeterministic` line in it does; `step`
+    /// still fires the first of them.
+    pub fn set_nondeterministic(&mut self, nondeterministic: bool) {
+        self.nondeterministic = nondeterministic;
+    }
+
+    /// Lets a rule for `Q0` fire in state `q0` when no rule names the state
+    /// exactly, and a halt line `QF` halt a machine in state `qf`. Only the
+    /// ASCII case is ignored.
+    pub fn set_ignore_state_case(&mut self, ignore: bool) {
+        self.ignore_state_case = ignore;
+    }
+
+    /// Runs the loaded machine on a new input, read as by `set_start`: the
+    /// head goes back to cell 0, or the `@` marker, and the state to the start
+    /// state, and `reset_to_start` will use it.
+    pub fn set_tape(&mut self, input: &str) -> Result<(), TmError> {
+        let state = self.initial_state.clone();
+        self.set_start(&state, input)
+    }
+
+    /// Starts the head on `cell` of the start tape and goes back to the
+    /// start, like `reset_to_start`, so that any tape a run left behind is
+    /// dropped.
+    pub fn set_head(&mut self, cell: usize) -> Result<(), TmError> {
+        let len = self.initial_tape.len();
+        if cell >= len {
+            return Err(TmError::HeadOutOfBounds { cell, len });
+        }
+
+        self.initial_tape_cell = cell;
+        self.reset_to_start();
+        Ok(())
+    }
+
+    /// Makes `state` the only halt state, a plain one: accept and reject
+    /// states are dropped along with the other halt states.
+    pub fn set_halt_state(&mut self, state: &str) {
+        self.halt_states.clear();
+        self.accept_states.clear();
+        self.reject_states.clear();
+        self.add_halt_state(state);
+    }
+
+    pub fn add_halt_state(&mut self, state: &str) {
+        self.halt_states.insert(state.to_string());
+    }
+
+    /// Accept and reject states halt the machine like any other halt state,
+    /// but also decide its `outcome`.
+    pub fn add_accept_state(&mut self, state: &str) {
+        self.add_halt_state(state);
+        self.accept_states.insert(state.to_string());
+    }
+
+    pub fn add_reject_state(&mut self, state: &str) {
+        self.add_halt_state(state);
+        self.reject_states.insert(state.to_string());
+    }
+
+    pub fn add_instruction(
+        &mut self,
+        current_state: &str,
+        current_symbol: char,
+        new_state: &str,
+        new_symbol: char,
+        direction: Direction,
+    ) {
+        self.instructions.push(Instruction {
+            current_state: current_state.to_string(),
+            current_symbol,
+            new_state: new_state.to_string(),
+            new_symbol,
+            direction,
+        });
+    }
+
+    /// Changes what the rule for (state, symbol) does. Returns false if there
+    /// was no such rule, in which case it is added. A `*` rule is only
+    /// replaced when `symbol` is `*` itself.
+    pub fn replace_instruction(
+        &mut self,
+        state: &str,
+        symbol: char,
+        new_state: &str,
+        new_symbol: char,
+        dir: Direction,
+    ) -> bool {
+        self.instructions.replace(Instruction {
+            current_state: state.to_string(),
+            current_symbol: symbol,
+            new_state: new_state.to_string(),
+            new_symbol,
+            direction: dir,
+        })
+    }
+
+    /// Deletes the rules for (state, symbol), all of them on a
+    /// nondeterministic machine. Returns false if there were none.
+    pub fn remove_instruction(&mut self, state: &str, symbol: char) -> bool {
+        self.instructions.remove(state, symbol)
+    }
+
+    /// Drops every rule but keeps the tape, state and halt states.
+    pub fn clear_instructions(&mut self) {
+        self.instructions.clear();
+    }
+
+    /// Runs `other` after this machine on the same tape. Every state of
+    /// `other` is renamed to `prefix` followed by its name and its rules are
+    /// added as they are. Rules of this machine that entered one of its halt
+    /// states enter the renamed start state of `other` instead, and the halt,
+    /// accept and reject states of this machine are replaced by the renamed
+    /// ones of `other`; a machine that starts halted starts in `other`. The
+    /// tape, head and blank of this machine are kept, so both must share the
+    /// blank.
+    pub fn append(&mut self, other: &TuringMachine, prefix: &str) -> Result<(), TmError> {
+        if other.blank != self.blank {
+            return Err(TmError::Validation(format!(
+                "blanks {:?} and {:?} differ",
+                self.symbol_name(self.blank),
+                other.symbol_name(other.blank),
+            )));
+        }
+
+        let rename = |state: &str| format!("{prefix}{state}");
+        let own = self.states();
+        if let Some(state) = other.states().iter().map(|s| rename(s)).find(|s| own.contains(s)) {
+            return Err(TmError::Validation(format!(
+                "state {state} exists in both machines",
+            )));
+        }
+
+        let mut symbols = HashMap::new();
+        for instruction in other.instructions.iter() {
+            for symbol in [instruction.current_symbol, instruction.new_symbol] {
+                if symbol != WILDCARD && !symbols.contains_key(&symbol) {
+                    let own = self.intern(&other.symbol_name(symbol)).map_err(TmError::Validation)?;
+                    symbols.insert(symbol, own);
+                }
+            }
+        }
+        let symbol = |c: char| symbols.get(&c).copied().unwrap_or(c);
+
+        let start = rename(&other.initial_state);
+        let instructions: Vec<Instruction> = std::mem::take(&mut self.instructions).into();
+        let mut instructions: Vec<Instruction> = instructions
+            .into_iter()
+            .map(|i| if self.halt_states.contains(&i.new_state) {
+                Instruction { new_state: start.clone(), ..i }
+            } else {
+                i
+            })
+            .collect();
+        instructions.extend(other.instructions.iter().map(|i| Instruction {
+            current_state: rename(&i.current_state),
+            current_symbol: symbol(i.current_symbol),
+            new_state: rename(&i.new_state),
+            new_symbol: symbol(i.new_symbol),
+            direction: i.direction,
+        }));
+        self.instructions = instructions.into();
+
+        if self.halt_states.contains(&self.initial_state) {
+            self.initial_state = start.clone();
+        }
+        if self.halt_states.contains(&self.state) {
+            self.state = start;
+        }
+
+        let renamed = |states: &BTreeSet<String>| states.iter().map(|s| rename(s)).collect();
+        self.halt_states = renamed(&other.halt_states);
+        self.accept_states = renamed(&other.accept_states);
+        self.reject_states = renamed(&other.reject_states);
+        if let Some(declared) = &mut self.declared_states {
+            declared.extend(other.states().iter().map(|s| rename(s)));
+        }
+        self.nondeterministic |= other.nondeterministic;
+
+        Ok(())
+    }
+
+    /// Loads and validates a machine in one go.
+    pub fn from_cfg(path: &str) -> Result<TuringMachine, TmError> {
+        let mut tm = TuringMachine::new();
+        tm.load_cfg(path)?;
+        tm.validate()?;
+        Ok(tm)
+    }
+
+    pub fn from_cfg_str(cfg: &str) -> Result<TuringMachine, TmError> {
+        let mut tm = TuringMachine::new();
+        tm.load_cfg_str(cfg)?;
+        tm.validate()?;
+        Ok(tm)
+    }
+
+    pub fn load_cfg(&mut self, path: &str) -> Result<(), TmError> {
+        let file = File::open(path).map_err(|e| {
+            io::Error::new(e.kind(), format!("failed to open {path}: {e}"))
+        })?;
+
+        self.load_cfg_reader(io::BufReader::new(file))
+    }
+
+    /// E.g. `cat machine.cfg | tms`; parses exactly like `load_cfg`.
+    pub fn load_cfg_stdin(&mut self) -> Result<(), TmError> {
+        self.load_cfg_reader(io::stdin().lock())
+    }
+
+    pub fn load_cfg_str(&mut self, cfg: &str) -> Result<(), TmError> {
+        self.load_cfg_reader(cfg.as_bytes())
+    }
+
+    pub fn load_cfg_reader<R: BufRead>(&mut self, reader: R) -> Result<(), TmError> {
+        let lines = reader.lines().collect::<Result<Vec<_>, _>>()?;
+        self.load_cfg_lines(numbered_lines(lines.iter().map(String::as_str)))
+    }
+
+    /// Loads every machine of a file holding several of them. Machines are
+    /// separated by `---` lines or start with a `machine <name>` header.
+    pub fn load_all(path: &str) -> Result<Vec<TuringMachine>, TmError> {
+        let text = fs::read_to_string(path).map_err(|e| {
+            io::Error::new(e.kind(), format!("failed to open {path}: {e}"))
+        })?;
+
+        let mut machines = Vec::new();
+        let mut section: Vec<(usize, &str)> = Vec::new();
+        let mut flush = |section: &mut Vec<(usize, &str)>| -> Result<(), TmError> {
+            if section.iter().any(|(_, line)| !strip_comment(line, 1).trim().is_empty()) {
+                let mut tm = TuringMachine::new();
+                tm.load_cfg_lines(section.drain(..))?;
+                machines.push(tm);
+            }
+            section.clear();
+            Ok(())
+        };
+
+        for (number, line) in numbered_lines(text.lines()) {
+            let content = strip_comment(line, 1).trim();
+            if content == MACHINE_SEPARATOR {
+                flush(&mut section)?;
+                continue;
+            }
+            if content.split_whitespace().next() == Some("machine") {
+                flush(&mut section)?;
+            }
+            section.push((number, line));
+        }
+        flush(&mut section)?;
+
+        Ok(machines)
+    }
+
+    /// Parses config lines paired with their line numbers.
+    fn load_cfg_lines<'a, I>(&mut self, lines: I) -> Result<(), TmError>
+    where
+        I: Iterator<Item = (usize, &'a str)>,
+    {
+        let mut has_start = false;
+        let mut has_marker = false;
+        let mut head: Option<(usize, usize)> = None;
+        let mut rule_lines: HashMap<(String, String), usize> = HashMap::new();
+        let mut rule_states: Vec<(usize, String)> = Vec::new();
+        let mut strict = false;
+        let mut duplicate = None;
+
+        for (number, line) in lines {
+            let line = strip_comment(line, 1).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let substrings = config_fields(number, line, 1)?;
+            if apply_shared_directive(self, number, line, &substrings)? {
+                continue;
+            }
+            let count = substrings.len();
+
+            match count {
+                2 if substrings[0] == "start" => {
+                    check_state_name(number, line, substrings[1])?;
+                    self.state = substrings[1].to_string();
+                    has_start = true;
+                }
+                _ if count > 1 && substrings[0] == "tape" => {
+                    self.tape = self.intern_cells(substrings[1..].to_vec())
+                        .map_err(|e| invalid_line(number, line, &e))?;
+                    self.tape_cell = 0;
+                    self.origin = 0;
+                }
+                _ if count > 1 && substrings[0] == "states" => {
+                    if !rule_lines.is_empty() {
+                        return Err(invalid_line(
+                            number,
+                            line,
+                            "states must be declared before the rules",
+                        ));
+                    }
+                    for state in &substrings[1..] {
+                        check_state_name(number, line, state)?;
+                    }
+                    self.declared_states
+                        .get_or_insert_with(BTreeSet::new)
+                        .extend(substrings[1..].iter().map(|s| s.to_string()));
+                }
+                2 if substrings[0] == "head" => {
+                    let cell = substrings[1]
+                        .parse()
+                        .map_err(|_| invalid_line(number, line, "expected a cell index"))?;
+                    head = Some((number, cell));
+                }
+                2 => {
+                    check_state_name(number, line, substrings[0])?;
+                    let (tape, cell) = self.parse_tape(substrings[1])
+                        .map_err(|e| invalid_line(number, line, &e))?;
+                    self.tape = tape;
+                    self.state = substrings[0].to_string();
+                    self.tape_cell = cell;
+                    self.origin = 0;
+                    has_marker = substrings[1].contains(HEAD_MARKER);
+                    has_start = true;
+                }
+                1 if substrings[0] == "strict" => {
+                    strict = true;
+                }
+                1 if substrings[0] == "nondeterministic" => {
+                    self.nondeterministic = true;
+                }
+                1 => check_state_name(number, line, substrings[0])?,
+                5 => {
+                    check_state_name(number, line, substrings[0])?;
+                    check_state_name(number, line, substrings[2])?;
+                    if let Some(declared) = &self.declared_states {
+                        if let Some(state) = [substrings[0], substrings[2]]
+                            .into_iter()
+                            .find(|s| !declared.contains(*s)) {
+                            return Err(invalid_line(
+                                number,
+                                line,
+                                &format!("undeclared state {state}"),
+                            ));
+                        }
+                    }
+
+                    rule_states.push((number, substrings[0].to_string()));
+                    rule_states.push((number, substrings[2].to_string()));
+
+                    let current_state = substrings[0].to_string();
+                    let current_symbol = self.intern(substrings[1])
+                        .map_err(|e| invalid_line(number, line, &e))?;
+                    let new_state = substrings[2].to_string();
+                    let new_symbol = self.intern(substrings[3])
+                        .map_err(|e| invalid_line(number, line, &e))?;
+                    let direction = Direction::str2dir(substrings[4])
+                        .ok_or_else(|| TmError::UnknownDirection {
+                            line: number,
+                            direction: substrings[4].to_string(),
+                        })?;
+
+                    if let Err(err) = check_duplicate(
+                        &mut rule_lines,
+                        &current_state,
+                        substrings[1],
+                        number,
+                    ) {
+                        duplicate.get_or_insert(err);
+                    }
+
+                    self.instructions.push(Instruction {
+                        current_state,
+                        current_symbol,
+                        new_state,
+                        new_symbol,
+                        direction,
+                    });
+                }
+                _ => {
+                    return Err(invalid_line(
+                        number,
+                        line,
+                        &format!("expected 1, 2, or 5 fields, found {count}"),
+                    ));
+                }
+            }
+        }
+
+        // A `nondeterministic` line may come after the rules it allows.
+        if let Some(err) = duplicate.filter(|_| !self.nondeterministic) {
+            return Err(err);
+        }
+
+        if !has_start {
+            return Err(TmError::MissingStartConfig);
+        }
+
+        // In strict mode every state of a rule must be listed by `states` or
+        // be a halt, accept or reject state, wherever those lines are.
+        if strict {
+            let is_declared = |state: &str| {
+                self.halt_states.contains(state)
+                    || self.declared_states.as_ref().is_some_and(|d| d.contains(state))
+            };
+            if let Some((number, state)) = rule_states.iter().find(|(_, s)| !is_declared(s)) {
+                return Err(TmError::Parse {
+                    line: *number,
+                    message: format!("state {state} is not declared (strict mode)"),
+                });
+            }
+        }
+
+        // Cells past the end of the input are filled with blanks.
+        if let Some((number, cell)) = head {
+            if has_marker {
+                return Err(TmError::Parse {
+                    line: number,
+                    message: format!(
+                        "head directive conflicts with the {HEAD_MARKER:?} marker of the start line"
+                    ),
+                });
+            }
+            if cell >= self.tape.len() {
+                self.tape.resize(cell + 1, self.blank);
+            }
+            self.tape_cell = cell;
+        }
+
+        self.save_initial();
+
+        Ok(())
+    }
+
+    pub fn save_cfg(&self, path: &str) -> Result<(), TmError> {
+        fs::write(path, self.to_cfg_string()).map_err(|e| {
+            io::Error::new(e.kind(), format!("failed to create {path}: {e}"))
+        })?;
+        Ok(())
+    }
+
+    /// Instructions in canonical order for saving and exporting: by state,
+    /// then by read symbol. The sort is stable, so rules sharing a state and
+    /// symbol keep the order in which `step` tries them.
+    fn sorted_instructions(&self) -> Vec<&Instruction> {
+        let mut instructions: Vec<&Instruction> = self.instructions.iter().collect();
+        instructions.sort_by_cached_key(|i| {
+            (i.current_state.clone(), self.symbol_name(i.current_symbol))
+        });
+        instructions
+    }
+
+    pub fn step(&mut self) -> StepOutcome {
+        if self.is_halt() {
+            return StepOutcome::Halted;
+        }
+
+        let Some(i) = self.find_transition() else {
+            #[cfg(feature = "logging")]
+            log::debug!(
+                "stuck in {} reading '{}' @cell {}",
+                self.state,
+                self.symbol_name(self.head_symbol()),
+                self.get_head_offset(),
+            );
+            return StepOutcome::NoTransition;
+        };
+
+        let state: String = self.instructions[i].new_state.clone();
+        let symbol: char = self.instructions[i].written(self.head_symbol());
+        let direction: Direction = self.instructions[i].direction;
+
+        if self.blocks_move(self.tape_cell, direction) {
+            #[cfg(feature = "logging")]
+            log::debug!("left bound violated in {} @cell 0", self.state);
+            return StepOutcome::LeftBoundViolation;
+        }
+
+        if self.max_tape_size.is_some_and(|max| self.grown_len(direction) > max) {
+            #[cfg(feature = "logging")]
+            log::debug!("tape limit reached in {}", self.state);
+            return StepOutcome::TapeLimitExceeded;
+        }
+
+        if self.history_capacity > 0 {
+            if self.history.len() == self.history_capacity {
+                self.history.pop_front();
+            }
+            self.history.push_back(HistoryEntry {
+                state: self.state.clone(),
+                head: self.get_head_offset(),
+                symbol: self.head_symbol(),
+                extended: false,
+                leftmost: self.leftmost,
+                rightmost: self.rightmost,
+            });
+        }
+
+        if self.steps == 0 {
+            self.visit();
+        }
+
+        #[cfg(feature = "logging")]
+        log::trace!(
+            "{} '{}' -> {} '{}' {} @cell {}",
+            self.state,
+            self.symbol_name(self.head_symbol()),
+            state,
+            self.symbol_name(symbol),
+            Direction::dir2str(&direction),
+            self.get_head_offset(),
+        );
+
+        let len = self.tape.len();
+        self.update(&state, symbol, direction); 
+        self.steps += 1;
+        self.visit();
+
+        let head = self.get_head_offset();
+        self.leftmost = self.leftmost.min(head);
+        self.rightmost = self.rightmost.max(head);
+
+        if let Some(entry) = self.history.back_mut() {
+            entry.extended = self.tape.len() > len;
+        }
+
+        #[cfg(feature = "logging")]
+        if self.is_halt() {
+            log::debug!("halted in {} after {} steps", self.state, self.steps);
+        }
+
+        StepOutcome::Applied
+    }
+
+    /// The tape length after writing under the head and moving it.
+    fn grown_len(&self, direction: Direction) -> usize {
+        let len = self.tape.len().max(self.tape_cell + 1);
+
+        match (self.tape_mode, direction) {
+            (TapeMode::Infinite, Direction::Lhs) if self.tape_cell == 0 => len + 1,
+            (TapeMode::Circular, _) | (_, Direction::Lhs) | (_, Direction::Stay) => len,
+            (_, Direction::Rhs) => len.max(self.tape_cell + 2),
+        }
+    }
+
+    fn blocks_move(&self, cell: usize, direction: Direction) -> bool {
+        self.tape_mode == TapeMode::LeftBounded && cell == 0 && direction == Direction::Lhs
+    }
+
+    /// Like `step`, but returns the index into `instructions()` of the rule
+    /// that fired, or None if no transition was applied. Indices are only
+    /// meaningful until the instruction list changes.
+    pub fn step_detailed(&mut self) -> Option<usize> {
+        let transition = self.find_transition();
+
+        match self.step() {
+            StepOutcome::Applied => transition,
+            _ => None,
+        }
+    }
+
+    /// Like `step`, but calls `observer` if a transition was applied.
+    pub fn step_observed<F>(&mut self, mut observer: F) -> StepOutcome
+    where
+        F: FnMut(&StepEvent),
+    {
+        let transition = self.find_transition();
+        let head_before = self.get_head_offset();
+
+        let outcome = self.step();
+        if let (StepOutcome::Applied, Some(i)) = (outcome, transition) {
+            observer(&StepEvent {
+                instruction: &self.instructions[i],
+                head_before,
+                state: &self.state,
+            });
+        }
+
+        outcome
+    }
+
+    /// Steps at most `n` times, stopping early when the machine halts or gets
+    /// stuck. Returns the number of applied steps and the last outcome, which
+    /// is `Halted` whenever the machine ends up in a halt state.
+    pub fn run_steps(&mut self, n: usize) -> (usize, StepOutcome) {
+        match run_with(self, n, |tm| Some(tm.step())) {
+            RunResult::Halted { steps } => (steps, StepOutcome::Halted),
+            RunResult::NoTransition { steps } => (steps, StepOutcome::NoTransition),
+            RunResult::LeftBoundViolation { steps } => (steps, StepOutcome::LeftBoundViolation),
+            RunResult::TapeLimitExceeded { steps } => (steps, StepOutcome::TapeLimitExceeded),
+            RunResult::StepLimitExceeded { steps }
+            | RunResult::Stopped { steps }
+            | RunResult::Breakpoint { steps, .. }
+            | RunResult::Watchpoint { steps, .. } => (steps, StepOutcome::Applied),
+        }
+    }
+
+    /// Undoes the last recorded transition. Returns false when the history is
+    /// empty, e.g. because it is disabled (the default capacity is 0).
+    /// The run statistics are rolled back along with the configuration.
+    pub fn step_back(&mut self) -> bool {
+        let Some(entry) = self.history.pop_back() else {
+            return false;
+        };
+
+        if entry.extended {
+            if self.get_head_offset() < entry.head {
+                self.tape.pop_front();
+                self.origin -= 1;
+            } else {
+                self.tape.pop();
+            }
+        }
+
+        if let Some(count) = self.visits.get_mut(&self.state) {
+            *count -= 1;
+            if *count == 0 {
+                self.visits.remove(&self.state);
+            }
+        }
+
+        self.tape_cell = (self.origin as isize + entry.head) as usize;
+        self.tape[self.tape_cell] = entry.symbol;
+        self.state = entry.state;
+        self.leftmost = entry.leftmost;
+        self.rightmost = entry.rightmost;
+        self.steps -= 1;
+
+        if self.steps == 0 {
+            self.visits.clear();
+        }
+
+        true
+    }
+
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.history_capacity = capacity;
+
+        while self.history.len() > capacity {
+            self.history.pop_front();
+        }
+    }
+
+    pub fn reset_to_start(&mut self) {
+        self.state = self.initial_state.clone();
+        self.tape = self.initial_tape.clone().into();
+        self.tape_cell = self.initial_tape_cell;
+        self.origin = 0;
+        self.clear_run();
+    }
+
+    /// Forgets everything recorded about the current run.
+    fn clear_run(&mut self) {
+        self.steps = 0;
+        self.history.clear();
+        self.visits.clear();
+        self.leftmost = self.get_head_offset();
+        self.rightmost = self.leftmost;
+    }
+
+    fn visit(&mut self) {
+        match self.visits.get_mut(&self.state) {
+            Some(count) => *count += 1,
+            None => {
+                self.visits.insert(self.state.clone(), 1);
+            }
+        }
+    }
+
+    pub fn stats(&self) -> RunStats {
+        RunStats {
+            steps: self.steps,
+            distinct_states: self.visits.len(),
+            visits: self.visits.clone(),
+            leftmost: self.leftmost,
+            rightmost: self.rightmost,
+            tape_len: self.tape.len(),
+        }
+    }
+
+    /// Also materializes the head cell of a tape marked as `ab@`, only now so
+    /// that it is filled with the final blank.
+    fn save_initial(&mut self) {
+        if self.tape_cell == self.tape.len() {
+            self.tape.push(self.blank);
+        }
+        self.initial_state = self.state.clone();
+        self.initial_tape = self.tape.to_vec();
+        self.initial_tape_cell = self.tape_cell;
+        self.clear_run();
+    }
+
+    pub fn validate(&self) -> Result<(), TmError> {
+        let is_source = |state: &str| self.instructions.has_state(state);
+
+        if !self.halt_states.contains(&self.initial_state)
+            && !is_source(&self.initial_state) {
+            return Err(TmError::Validation(format!(
+                "start state {} has no outgoing instruction",
+                self.initial_state,
+            )));
+        }
+
+        if let Some(state) = self.halt_states.iter().find(|s| is_source(s)) {
+            return Err(TmError::Validation(format!(
+                "halt state {state} has outgoing instructions",
+            )));
+        }
+
+        for instruction in self.instructions.iter() {
+            let state = &instruction.new_state;
+
+            if !self.halt_states.contains(state) && !is_source(state) {
+                return Err(TmError::Validation(format!(
+                    "state {state} is neither a halt state nor has outgoing instructions",
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every non-blank symbol of the initial tape is read by some
+    /// instruction, which catches typos in the input before running.
+    pub fn validate_input(&self) -> Result<(), TmError> {
+        let read: HashSet<char> = self.instructions
+            .iter()
+            .map(|i| i.current_symbol)
+            .collect();
+        if read.contains(&WILDCARD) {
+            return Ok(());
+        }
+
+        match self.initial_tape
+            .iter()
+            .position(|c| *c != self.blank && !read.contains(c)) {
+            Some(cell) => Err(TmError::UnexpectedSymbol {
+                symbol: self.symbol_name(self.initial_tape[cell]),
+                cell,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The pending transition as a sentence, e.g. "In state q0 reading 1:
+    /// write 0, move right, enter q1". None when the machine is stuck.
+    pub fn describe_next(&self) -> Option<String> {
+        if self.is_halt() {
+            return Some(format!("Halted in state {}", self.state));
+        }
+
+        let instruction = self.peek_transition()?;
+        let read = self.head_symbol();
+        let movement = match instruction.direction {
+            Direction::Stay => "stay".to_string(),
+            dir => format!("move {}", Direction::dir2str(&dir)),
+        };
+
+        Some(format!(
+            "In state {} reading {}: write {}, {movement}, enter {}",
+            self.state,
+            self.symbol_name(read),
+            self.symbol_name(instruction.written(read)),
+            instruction.new_state,
+        ))
+    }
+
+    pub fn peek_transition(&self) -> Option<&Instruction> {
+        if self.is_halt() {
+            return None;
+        }
+
+        self.find_transition().map(|i| &self.instructions[i])
+    }
+
+    /// The rule `step` would fire in `state` reading `symbol`, whatever the
+    /// current configuration is. Halt states are not special here.
+    pub fn get_instruction_for(&self, state: &str, symbol: char) -> Option<&Instruction> {
+        self.rule_for(state, symbol).map(|i| &self.instructions[i])
+    }
+
+    /// Every rule for (state, symbol), for nondeterministic machines.
+    pub fn get_instructions_for(&self, state: &str, symbol: char) -> Vec<&Instruction> {
+        self.instructions.matching(state, symbol).collect()
+    }
+
+    fn find_transition(&self) -> Option<usize> {
+        self.rule_for(&self.state, self.head_symbol())
+    }
+
+    fn rule_for(&self, state: &str, symbol: char) -> Option<usize> {
+        let exact = self.instructions.find(state, symbol);
+        if exact.is_some() || !self.ignore_state_case {
+            return exact;
+        }
+
+        self.instructions
+            .iter()
+            .filter(|i| i.current_state.eq_ignore_ascii_case(state))
+            .find_map(|i| self.instructions.find(&i.current_state, symbol))
+    }
+
+    pub fn run(&mut self, max_steps: usize) -> Result<usize, TmError> {
+        run_to_halt(self, max_steps)
+    }
+
+    /// Like `run`, but bounded by wall-clock time rather than steps. The clock
+    /// is only read every `TIME_CHECK_INTERVAL` steps, so the budget may be
+    /// overrun by that many steps.
+    pub fn run_for(&mut self, budget: Duration) -> Result<usize, TmError> {
+        let start = Instant::now();
+        let mut steps = 0;
+
+        self.run_checked(usize::MAX, |tm| {
+            if steps % TIME_CHECK_INTERVAL == 0 && start.elapsed() >= budget {
+                return Err(TmError::TimedOut(steps));
+            }
+            steps += 1;
+            Ok(tm.step())
+        })
+    }
+
+    /// Like `run`, but sends a snapshot after every applied transition, e.g.
+    /// to a UI thread. Stops early, without error, once the receiver is gone.
+    pub fn run_streaming(
+        &mut self,
+        max_steps: usize,
+        tx: Sender<Configuration>,
+    ) -> Result<usize, TmError> {
+        let mut connected = true;
+        let result = run_with(self, max_steps, |tm| {
+            if !connected {
+                return None;
+            }
+            let outcome = tm.step();
+            if outcome == StepOutcome::Applied {
+                connected = tx.send(tm.snapshot()).is_ok();
+            }
+            Some(outcome)
+        });
+
+        match result {
+            RunResult::StepLimitExceeded { steps } if !connected => Ok(steps),
+            result => finish(self, result),
+        }
+    }
+
+    /// Like `run`, but records every applied transition, e.g. to be
+    /// serialized as a JSON trace for a visualizer.
+    #[cfg(feature = "serde")]
+    pub fn run_trace(&mut self, max_steps: usize) -> Result<Vec<TraceEntry>, TmError> {
+        let mut trace = Vec::new();
+
+        self.run_checked(max_steps, |tm| {
+            let Some(instruction) = tm.peek_transition().cloned() else {
+                return Ok(tm.step());
+            };
+            let state = tm.state.clone();
+            let head = tm.get_head_offset();
+            let read = tm.head_symbol();
+
+            let outcome = tm.step();
+            if outcome == StepOutcome::Applied {
+                trace.push(TraceEntry {
+                    step: tm.steps,
+                    state,
+                    head,
+                    read: tm.symbol_name(read),
+                    written: tm.symbol_name(instruction.written(read)),
+                    direction: instruction.direction,
+                    new_state: instruction.new_state,
+                });
+            }
+            Ok(outcome)
+        })?;
+
+        Ok(trace)
+    }
+
+    /// Like `run`, but copies the whole tape before the first step and after
+    /// every applied one. Each copy costs the full tape length, so keep
+    /// `max_steps` small.
+    pub fn run_tape_history(&mut self, max_steps: usize) -> Result<Vec<Vec<char>>, TmError> {
+        let mut tapes = vec![self.tape.to_vec()];
+
+        self.run_checked(max_steps, |tm| {
+            let outcome = tm.step();
+            if outcome == StepOutcome::Applied {
+                tapes.push(tm.tape.to_vec());
+            }
+            Ok(outcome)
+        })?;
+
+        Ok(tapes)
+    }
+
+    /// Like `run`, but calls `observer` after every applied transition.
+    pub fn run_with_observer<F>(&mut self, max_steps: usize, mut observer: F) -> Result<usize, TmError>
+    where
+        F: FnMut(&StepEvent),
+    {
+        self.run_checked(max_steps, |tm| Ok(tm.step_observed(&mut observer)))
+    }
+
+    /// Like `run`, but fails with `LoopDetected` as soon as a configuration
+    /// repeats, comparing them by `config_hash`. At most `memory` hashes are
+    /// remembered at a time and the set starts over once full, so cycles
+    /// longer than that go unnoticed.
+    pub fn run_detect_loop(
+        &mut self,
+        max_steps: usize,
+        memory: usize,
+    ) -> Result<usize, TmError> {
+        let mut seen: HashSet<u64> = HashSet::new();
+
+        self.run_checked(max_steps, |tm| {
+            let hash = tm.config_hash();
+
+            if seen.contains(&hash) {
+                return Err(TmError::LoopDetected(tm.steps));
+            }
+
+            if seen.len() >= memory {
+                seen.clear();
+            }
+            seen.insert(hash);
+            Ok(tm.step())
+        })
+    }
+
+    /// Steps until the machine halts, advancing it through `step` so callers
+    /// can check or observe every transition. An error from `step` ends the
+    /// run with that error.
+    fn run_checked<F>(&mut self, max_steps: usize, mut step: F) -> Result<usize, TmError>
+    where
+        F: FnMut(&mut TuringMachine) -> Result<StepOutcome, TmError>,
+    {
+        let mut error = None;
+        let result = run_with(self, max_steps, |tm| {
+            step(tm).map_err(|err| error = Some(err)).ok()
+        });
+
+        match error {
+            Some(err) => Err(err),
+            None => finish(self, result),
+        }
+    }
+
+    /// Steps for as long as `pred` holds, checking it before every step.
+    /// Stops without error once it fails or the machine halts.
+    pub fn step_while<F>(&mut self, max_steps: usize, pred: F) -> Result<usize, TmError>
+    where
+        F: Fn(&TuringMachine) -> bool,
+    {
+        match self.run_until(max_steps, |tm| !pred(tm)) {
+            RunResult::Stopped { steps } | RunResult::Halted { steps } => Ok(steps),
+            RunResult::StepLimitExceeded { .. } => Err(TmError::StepLimitExceeded(max_steps)),
+            RunResult::LeftBoundViolation { .. } => {
+                Err(self.stop_error(StepOutcome::LeftBoundViolation))
+            }
+            RunResult::TapeLimitExceeded { .. } => {
+                Err(self.stop_error(StepOutcome::TapeLimitExceeded))
+            }
+            _ => Err(self.stop_error(StepOutcome::NoTransition)),
+        }
+    }
+
+    /// Pauses `run_until_breakpoint` whenever the machine enters `state`.
+    pub fn add_breakpoint(&mut self, state: &str) {
+        self.breakpoints.insert(state.to_string());
+    }
+
+    pub fn remove_breakpoint(&mut self, state: &str) -> bool {
+        self.breakpoints.remove(state)
+    }
+
+    /// Like `add_breakpoint`, but only pauses in `state` while the head reads
+    /// `symbol`, just before the matching rule fires.
+    pub fn add_breakpoint_sym(&mut self, state: &str, symbol: char) {
+        self.symbol_breakpoints.insert((state.to_string(), symbol));
+    }
+
+    pub fn remove_breakpoint_sym(&mut self, state: &str, symbol: char) -> bool {
+        self.symbol_breakpoints.remove(&(state.to_string(), symbol))
+    }
+
+    /// Pauses `run_until_breakpoint` after a transition changes the symbol at
+    /// this offset from input cell 0, as `get_head_offset` counts them, so
+    /// cells left of the input are negative. The offset stays put when the
+    /// tape grows leftwards.
+    pub fn watch_cell(&mut self, cell: isize) {
+        self.watched_cells.insert(cell);
+    }
+
+    pub fn unwatch_cell(&mut self, cell: isize) -> bool {
+        self.watched_cells.remove(&cell)
+    }
+
+    fn breakpoint_hit(&self, steps: usize) -> Option<RunResult> {
+        let symbol = self.head_symbol();
+
+        if self.symbol_breakpoints.contains(&(self.state.clone(), symbol)) {
+            Some(RunResult::Breakpoint {
+                state: self.state.clone(),
+                symbol: Some(symbol),
+                steps,
+            })
+        } else if self.breakpoints.contains(&self.state) {
+            Some(RunResult::Breakpoint {
+                state: self.state.clone(),
+                symbol: None,
+                steps,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Steps until a breakpoint is hit, the machine halts, gets stuck or runs
+    /// out of steps. Breakpoints are checked before every transition but the
+    /// first, so calling this again from a breakpoint resumes the run.
+    pub fn run_until_breakpoint(&mut self, max_steps: usize) -> RunResult {
+        let mut steps = 0;
+        let mut last_write: Option<(isize, char)> = None;
+        let mut hit = None;
+
+        let result = self.run_until(max_steps, |tm| {
+            if let Some((cell, old)) = last_write {
+                let new = tm.cell_at(cell);
+                if new != old && tm.watched_cells.contains(&cell) {
+                    hit = Some(RunResult::Watchpoint { cell, old, new, steps });
+                    return true;
+                }
+            }
+            if steps > 0 {
+                hit = tm.breakpoint_hit(steps);
+                if hit.is_some() {
+                    return true;
+                }
+            }
+
+            last_write = Some((tm.get_head_offset(), tm.head_symbol()));
+            steps += 1;
+            false
+        });
+
+        match result {
+            RunResult::Stopped { .. } => hit.unwrap(),
+            result => result,
+        }
+    }
+
+    /// Steps until `stop` returns true, the machine halts, gets stuck or runs
+    /// out of steps. `stop` is called before every step and once more after
+    /// the last one, even if the machine halted.
+    pub fn run_until<F>(&mut self, max_steps: usize, mut stop: F) -> RunResult
+    where
+        F: FnMut(&TuringMachine) -> bool,
+    {
+        let result = run_with(self, max_steps, |tm| {
+            if stop(tm) {
+                None
+            } else {
+                Some(tm.step())
+            }
+        });
+
+        match result {
+            RunResult::Halted { steps } | RunResult::StepLimitExceeded { steps } if stop(self) => {
+                RunResult::Stopped { steps }
+            }
+            result => result,
+        }
+    }
+
+    /// Runs both machines from their start states on `input`, which becomes
+    /// their start tape as with `set_tape`, and compares whether they halt with
+    /// the same outcome and the same trimmed tape. Fails only if `input` does
+    /// not make a tape.
+    pub fn run_equivalent(
+        &mut self,
+        other: &mut TuringMachine,
+        input: &str,
+        max_steps: usize,
+    ) -> Result<Equivalence, TmError> {
+        type Halt = (Option<Outcome>, Vec<String>);
+
+        fn result(
+            tm: &mut TuringMachine,
+            input: &str,
+            max_steps: usize,
+        ) -> Result<Option<Halt>, TmError> {
+            tm.set_tape(input)?;
+            Ok(match tm.run(max_steps) {
+                Err(TmError::StepLimitExceeded(_)) => None,
+                _ => Some((
+                    tm.outcome(),
+                    tm.trimmed_tape().iter().map(|&c| tm.symbol_name(c)).collect(),
+                )),
+            })
+        }
+
+        let results = (result(self, input, max_steps)?, result(other, input, max_steps)?);
+        Ok(match results {
+            (Some((outcome, tape)), Some((other_outcome, other_tape))) => {
+                if outcome != other_outcome {
+                    Equivalence::DifferentOutcome
+                } else if tape != other_tape {
+                    Equivalence::DifferentTape
+                } else {
+                    Equivalence::Equivalent
+                }
+            }
+            (Some(_), None) => Equivalence::OnlyFirstHalts,
+            (None, Some(_)) => Equivalence::OnlySecondHalts,
+            (None, None) => Equivalence::NeitherHalts,
+        })
+    }
+
+    /// Breadth-first search over every branch of a nondeterministic machine,
+    /// starting from the current configuration. Returns the configurations
+    /// along the shortest path to an accepting halt state, or None if no
+    /// branch accepts within `max_steps` transitions. Branches that reach a
+    /// reject state end there.
+    pub fn run_nondeterministic(&self, max_steps: usize) -> Option<Vec<Configuration>> {
+        let start = self.snapshot();
+
+        // Every reached configuration with the index of its predecessor.
+        let mut nodes: Vec<(Configuration, Option<usize>)> = vec![(start.clone(), None)];
+        let mut visited: HashSet<Configuration> = HashSet::from([start]);
+        let mut frontier = vec![0];
+
+        for depth in 0..=max_steps {
+            let mut next = Vec::new();
+
+            for &node in &frontier {
+                let cfg = nodes[node].0.clone();
+
+                if self.in_states(&self.reject_states, &cfg.state) {
+                    continue;
+                }
+                if self.in_states(&self.halt_states, &cfg.state) {
+                    let mut path = vec![];
+                    let mut current = Some(node);
+                    while let Some(i) = current {
+                        path.push(nodes[i].0.clone());
+                        current = nodes[i].1;
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+
+                if depth == max_steps {
+                    continue;
+                }
+
+                let symbol = cfg.tape.get(cfg.tape_cell).copied().unwrap_or(self.blank);
+                for instruction in self.instructions.matching(&cfg.state, symbol) {
+                    if self.blocks_move(cfg.tape_cell, instruction.direction) {
+                        continue;
+                    }
+
+                    let mut tape = Cells::from(cfg.tape.clone());
+                    let mut tape_cell = cfg.tape_cell;
+                    let mut origin = cfg.origin;
+                    if tape_cell >= tape.len() {
+                        tape.resize(tape_cell + 1, self.blank);
+                    }
+                    tape[tape_cell] = instruction.written(symbol);
+                    shift(
+                        &mut tape,
+                        &mut tape_cell,
+                        &mut origin,
+                        self.blank,
+                        instruction.direction,
+                        self.tape_mode,
+                    );
+
+                    let successor = Configuration {
+                        state: instruction.new_state.clone(),
+                        tape: tape.to_vec(),
+                        tape_cell,
+                        origin,
+                    };
+
+                    if visited.insert(successor.clone()) {
+                        next.push(nodes.len());
+                        nodes.push((successor, Some(node)));
+                    }
+                }
+            }
+
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+
+        None
+    }
+
+    pub fn configurations(&mut self) -> Configurations<'_> {
+        self.configurations_bounded(usize::MAX)
+    }
+
+    /// Iterates over the configuration before each step, ending with the one
+    /// the machine halts or gets stuck in, or after `max_steps` transitions.
+    pub fn configurations_bounded(&mut self, max_steps: usize) -> Configurations<'_> {
+        Configurations {
+            tm: self,
+            remaining: max_steps,
+            done: false,
+        }
+    }
+
+    pub fn snapshot(&self) -> Configuration {
+        Configuration {
+            state: self.state.clone(),
+            tape: self.tape.to_vec(),
+            tape_cell: self.tape_cell,
+            origin: self.origin,
+        }
+    }
+
+    /// The configuration `reset_to_start` goes back to: the loaded input, or
+    /// the last one given to `set_tape`, `set_start` or `set_head`.
+    pub fn initial_configuration(&self) -> Configuration {
+        Configuration {
+            state: self.initial_state.clone(),
+            tape: self.initial_tape.clone(),
+            tape_cell: self.initial_tape_cell,
+            origin: 0,
+        }
+    }
+
+    /// FNV-1a hash of the state, the head offset and the symbol names of the
+    /// non-blank stretch of the tape, so logically equal configurations hash
+    /// equally no matter how far the tape was materialized or in which order
+    /// symbols were interned. Stable across runs and platforms.
+    pub fn config_hash(&self) -> u64 {
+        let mut hash = FNV_OFFSET;
+        let mut feed = |bytes: &[u8]| {
+            for &byte in bytes {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        };
+
+        let (start, end) = self.trimmed_range();
+        feed(self.state.as_bytes());
+        feed(&[0xff]);
+        feed(&(self.get_head_offset() as i64).to_le_bytes());
+        if start < end {
+            feed(&(start as i64 - self.origin as i64).to_le_bytes());
+            for &c in &self.tape[start..end] {
+                feed(self.symbol_name(c).as_bytes());
+                feed(&[0xff]);
+            }
+        }
+
+        hash
+    }
+
+    /// Puts back a snapshot. The step history no longer applies afterwards and
+    /// is dropped, so `step_back` cannot walk past a restore.
+    pub fn restore(&mut self, cfg: &Configuration) {
+        self.state = cfg.state.clone();
+        self.tape = cfg.tape.clone().into();
+        self.tape_cell = cfg.tape_cell;
+        self.origin = cfg.origin;
+        self.history.clear();
+    }
+
+    pub fn is_halt(&self) -> bool {
+        self.in_states(&self.halt_states, &self.state)
+    }
+
+    /// Whether `state` is one of `states`, ignoring its case if
+    /// `set_ignore_state_case` is on.
+    fn in_states(&self, states: &BTreeSet<String>, state: &str) -> bool {
+        states.contains(state)
+            || self.ignore_state_case && states.iter().any(|s| s.eq_ignore_ascii_case(state))
+    }
+
+    /// True when the machine stopped outside a halt state because no rule
+    /// applies to its state and the symbol under the head.
+    pub fn is_stuck(&self) -> bool {
+        !self.is_halt() && self.peek_transition().is_none()
+    }
+
+    /// None while the machine is running. Halt states that are declared
+    /// neither `accept` nor `reject` count as accepting.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if !self.is_halt() {
+            None
+        } else if self.in_states(&self.reject_states, &self.state) {
+            Some(Outcome::Reject)
+        } else {
+            Some(Outcome::Accept)
+        }
+    }
+
+    /// The `outcome` as a process exit code: 0 for accept, 1 for reject and 2
+    /// while the machine has not halted, stuck or not.
+    pub fn exit_code(&self) -> i32 {
+        match self.outcome() {
+            Some(Outcome::Accept) => 0,
+            Some(Outcome::Reject) => 1,
+            None => 2,
+        }
+    }
+
+    /// The tape without its leading and trailing blanks; empty if all blank.
+    pub fn trimmed_tape(&self) -> &[char] {
+        let (start, end) = self.trimmed_range();
+        &self.tape[start..end]
+    }
+
+    /// The result left on the tape: the trimmed tape, with its interior blanks
+    /// dropped too unless `include_blanks` is set.
+    pub fn output(&self, include_blanks: bool) -> String {
+        self.trimmed_tape()
+            .iter()
+            .filter(|&&c| include_blanks || c != self.blank)
+            .map(|&c| self.symbol_name(c))
+            .collect()
+    }
+
+    fn trimmed_range(&self) -> (usize, usize) {
+        let start = self
+            .tape
+            .iter()
+            .position(|&c| c != self.blank)
+            .unwrap_or(self.tape.len());
+        let end = self
+            .tape
+            .iter()
+            .rposition(|&c| c != self.blank)
+            .map_or(start, |i| i + 1);
+        (start, end)
+    }
+
+    /// Renders at most `window` cells of the trimmed tape around the head, e.g.
+    /// `...0 1 [1] 0...`, with `...` marking cells cut off on either side. The
+    /// head is always shown, even when it sits on a blank outside the content.
+    pub fn render_tape(&self, window: usize) -> String {
+        let window = window.max(1);
+        let (lo, hi) = match self.trimmed_range() {
+            (first, last) if first < last => {
+                (first.min(self.tape_cell), last.max(self.tape_cell + 1))
+            }
+            _ => (self.tape_cell, self.tape_cell + 1),
+        };
+
+        let start = self.tape_cell.saturating_sub(window / 2).max(lo);
+        let end = (start + window).min(hi);
+        let start = start.min(end.saturating_sub(window)).max(lo);
+
+        let cells = (start..end)
+            .map(|i| {
+                let name = self.symbol_name(self.tape.get(i).copied().unwrap_or(self.blank));
+                if i == self.tape_cell {
+                    format!("[{name}]")
+                } else {
+                    name
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!(
+            "{}{cells}{}",
+            if start > lo { "..." } else { "" },
+            if end < hi { "..." } else { "" },
+        )
+    }
+
+    /// Skips rewriting an unchanged state or cell, which is what `*` in the
+    /// new symbol column amounts to.
+    fn update(&mut self, new_state: &str, new_symbol: char, dir: Direction) {
+        if self.state != new_state {
+            self.set_state(new_state);
+        }
+        if self.tape.get(self.tape_cell) != Some(&new_symbol) {
+            self.write(new_symbol);
+        }
+        self.move_head(dir);
+    }
+
+    /// The symbol under the head. Cells past the end of the tape, such as the
+    /// first cell of an empty input, read as blank until written.
+    pub fn head_symbol(&self) -> char {
+        self.tape.get(self.tape_cell).copied().unwrap_or(self.blank)
+    }
+
+    pub fn write(&mut self, symbol: char) {
+        if self.tape_cell >= self.tape.len() {
+            self.tape.resize(self.tape_cell + 1, self.blank);
+        }
+
+        self.tape[self.tape_cell] = symbol;
+    }
+
+    pub fn move_head(&mut self, dir: Direction) {
+        shift(
+            &mut self.tape,
+            &mut self.tape_cell,
+            &mut self.origin,
+            self.blank,
+            dir,
+            self.tape_mode,
+        );
+    }
+
+    pub fn set_state(&mut self, state: &str) {
+        self.state = state.to_string();
+    }
+
+    /// Forgets the loaded machine along with every setting, such as
+    /// nondeterminism, the tape mode, breakpoints and the history capacity,
+    /// leaving the machine as `new` creates it.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Summary of the current run. Visits count the start state once and every
+/// state entered by a transition; head offsets are relative to the input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RunStats {
+    pub steps: usize,
+    pub distinct_states: usize,
+    pub visits: BTreeMap<String, usize>,
+    pub leftmost: isize,
+    pub rightmost: isize,
+    pub tape_len: usize,
+}
+
+impl fmt::Display for RunStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "steps: {}", self.steps)?;
+        writeln!(f, "distinct states: {}", self.distinct_states)?;
+        writeln!(f, "visits: {}", self.visits
+            .iter()
+            .map(|(state, count)| format!("{state} {count}"))
+            .collect::<Vec<_>>()
+            .join(", "))?;
+        writeln!(f, "head range: {}..={}", self.leftmost, self.rightmost)?;
+        write!(f, "tape length: {}", self.tape_len)
+    }
+}
+
+/// Machines are equal when they are in the same configuration and define the
+/// same transitions. Steps, history and instruction order are ignored, and
+/// tapes only differ where a cell that is not blank does. Symbols compare by
+/// name, so machines that interned their multi-character symbols in another
+/// order can still be equal.
+impl PartialEq for TuringMachine {
+    fn eq(&self, other: &Self) -> bool {
+        fn sorted(tm: &TuringMachine) -> Vec<(&str, String, &str, String, String)> {
+            let mut instructions: Vec<_> = tm.instructions
+                .iter()
+                .map(|i| (
+                    i.current_state.as_str(),
+                    tm.symbol_name(i.current_symbol),
+                    i.new_state.as_str(),
+                    tm.symbol_name(i.new_symbol),
+                    Direction::dir2str(&i.direction),
+                ))
+                .collect();
+            instructions.sort();
+            instructions
+        }
+
+        let start = (-(self.origin as isize)).min(-(other.origin as isize));
+        let end = (self.tape.len() as isize - self.origin as isize)
+            .max(other.tape.len() as isize - other.origin as isize);
+
+        self.state == other.state
+            && self.halt_states == other.halt_states
+            && self.accept_states == other.accept_states
+            && self.reject_states == other.reject_states
+            && self.symbol_name(self.blank) == other.symbol_name(other.blank)
+            && self.get_head_offset() == other.get_head_offset()
+            && (start..end).all(|offset| {
+                self.symbol_name(self.cell_at(offset)) == other.symbol_name(other.cell_at(offset))
+            })
+            && sorted(self) == sorted(other)
+    }
+}
+
+pub struct Configurations<'a> {
+    tm: &'a mut TuringMachine,
+    remaining: usize,
+    done: bool,
+}
+
+impl Iterator for Configurations<'_> {
+    type Item = Configuration;
+
+    fn next(&mut self) -> Option<Configuration> {
+        if self.done {
+            return None;
+        }
+
+        let configuration = self.tm.snapshot();
+
+        if self.remaining == 0 || self.tm.step() != StepOutcome::Applied {
+            self.done = true;
+        } else {
+            self.remaining -= 1;
+        }
+
+        Some(configuration)
+    }
+}
+
+impl fmt::Display for TuringMachine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = &self.name {
+            writeln!(f, "name: {name}")?;
+        }
+        writeln!(f, "state: {}", self.state)?;
+        writeln!(f, "halt states: {}", self.halt_states
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", "))?;
+        writeln!(f, "head: {}", self.get_head_offset())?;
+        writeln!(f, "tape: {}", self.render_tape(DISPLAY_WINDOW))?;
+        write!(f, "instructions: {}", self.instructions.len())
+    }
+}
+
+/// A one-line summary for `dbg!` and failed assertions; `Display` is the
+/// multi-line view for users.
+impl fmt::Debug for TuringMachine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TuringMachine")
+            .field("state", &self.state)
+            .field("halt_states", &self.halt_states)
+            .field("head", &self.get_head_offset())
+            .field("tape", &self.render_tape(DISPLAY_WINDOW))
+            .field("instructions", &self.instructions.len())
+            .finish()
     }
 }
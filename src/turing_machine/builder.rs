@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use super::{check_duplicate, Direction, TmError, TuringMachine};
+
+/// Chainable alternative to the setters, e.g.
+/// `TuringMachineBuilder::new().start("q0", "1").halt("qf").rule(...).build()`.
+/// `build` runs the same checks as loading a config followed by `validate`.
+/// The start tape is only read by `build`, so it gets the blank however the
+/// calls are ordered and a bad one is reported there; a `DuplicateInstruction`
+/// error numbers the rules from 1 in the order they were added instead of
+/// giving config lines.
+#[derive(Default)]
+pub struct TuringMachineBuilder {
+    tm: TuringMachine,
+    start: Option<(String, String)>,
+}
+
+impl TuringMachineBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(mut self, state: &str, tape: &str) -> Self {
+        self.start = Some((state.to_string(), tape.to_string()));
+        self
+    }
+
+    pub fn halt(mut self, state: &str) -> Self {
+        self.tm.add_halt_state(state);
+        self
+    }
+
+    pub fn accept(mut self, state: &str) -> Self {
+        self.tm.add_accept_state(state);
+        self
+    }
+
+    pub fn reject(mut self, state: &str) -> Self {
+        self.tm.add_reject_state(state);
+        self
+    }
+
+    pub fn blank(mut self, blank: char) -> Self {
+        self.tm.blank = blank;
+        self
+    }
+
+    pub fn nondeterministic(mut self, nondeterministic: bool) -> Self {
+        self.tm.set_nondeterministic(nondeterministic);
+        self
+    }
+
+    pub fn rule(
+        mut self,
+        current_state: &str,
+        current_symbol: char,
+        new_state: &str,
+        new_symbol: char,
+        direction: Direction,
+    ) -> Self {
+        self.tm.add_instruction(current_state, current_symbol, new_state, new_symbol, direction);
+        self
+    }
+
+    pub fn build(mut self) -> Result<TuringMachine, TmError> {
+        let Some((state, tape)) = &self.start else {
+            return Err(TmError::MissingStartConfig);
+        };
+        self.tm.set_start(state, tape)?;
+
+        if !self.tm.nondeterministic {
+            let mut seen = HashMap::new();
+            for (i, instruction) in self.tm.instructions.iter().enumerate() {
+                check_duplicate(
+                    &mut seen,
+                    &instruction.current_state,
+                    &self.tm.symbol_name(instruction.current_symbol),
+                    i + 1,
+                )?;
+            }
+        }
+
+        self.tm.validate()?;
+        Ok(self.tm)
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt,
+};
+
+use super::{TmError, TuringMachine, WILDCARD};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum WarningKind {
+    UnreachableState,
+    NoOutgoingRule,
+    SymbolNeverWritten,
+    HaltStateAsSource,
+}
+
+/// A problem that does not stop the machine from loading but likely is a
+/// mistake in the config.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl TuringMachine {
+    /// Loads a config and lists everything `warnings` finds in it. Only
+    /// errors that keep the config from loading are returned as `Err`.
+    pub fn check_cfg(path: &str) -> Result<Vec<Warning>, TmError> {
+        let mut tm = TuringMachine::new();
+        tm.load_cfg(path)?;
+        Ok(tm.warnings())
+    }
+
+    /// Unlike `validate`, collects every problem instead of stopping at the
+    /// first one. Symbols on the input or blank are never reported as unwritten.
+    pub fn warnings(&self) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        let mut warn = |kind, message: String| warnings.push(Warning { kind, message });
+
+        let reachable = self.reachable_states();
+        for state in self.states() {
+            if !reachable.contains(&state) {
+                warn(WarningKind::UnreachableState, format!("state {state} is unreachable"));
+            }
+
+            let is_source = self.instructions.has_state(&state);
+            if self.halt_states.contains(&state) {
+                if is_source {
+                    warn(
+                        WarningKind::HaltStateAsSource,
+                        format!("halt state {state} has outgoing instructions"),
+                    );
+                }
+            } else if !is_source {
+                warn(
+                    WarningKind::NoOutgoingRule,
+                    format!("state {state} has no outgoing instruction"),
+                );
+            }
+        }
+
+        let written: BTreeSet<char> = self.instructions
+            .iter()
+            .map(|i| i.new_symbol)
+            .chain(self.initial_tape.iter().copied())
+            .chain([self.blank])
+            .collect();
+        let read: BTreeSet<char> = self.instructions
+            .iter()
+            .map(|i| i.current_symbol)
+            .filter(|&c| c != WILDCARD)
+            .collect();
+        for symbol in read.difference(&written) {
+            warn(
+                WarningKind::SymbolNeverWritten,
+                format!("symbol {:?} is read but never written", self.symbol_name(*symbol)),
+            );
+        }
+
+        warnings
+    }
+
+    /// No two rules share a state and read symbol. A `*` rule next to exact
+    /// rules of the same state does not count, as exact rules win.
+    pub fn is_deterministic(&self) -> bool {
+        let mut seen = HashSet::new();
+        self.instructions
+            .iter()
+            .all(|i| seen.insert((i.current_state.as_str(), i.current_symbol)))
+    }
+
+    /// Every reachable state that does not halt has a rule for every symbol
+    /// of the alphabet, either exactly or through `*`.
+    pub fn is_total(&self) -> bool {
+        let alphabet = self.alphabet();
+
+        self.reachable_states()
+            .iter()
+            .filter(|state| !self.halt_states.contains(*state))
+            .all(|state| {
+                alphabet
+                    .iter()
+                    .all(|&symbol| self.rule_for(state, symbol).is_some())
+            })
+    }
+
+    fn reachable_states(&self) -> BTreeSet<String> {
+        let mut reachable = BTreeSet::new();
+        let mut pending = vec![self.initial_state.clone()];
+
+        while let Some(state) = pending.pop() {
+            if !reachable.insert(state.clone()) {
+                continue;
+            }
+            for instruction in self.instructions.iter() {
+                if instruction.current_state == state {
+                    pending.push(instruction.new_state.clone());
+                }
+            }
+        }
+
+        reachable
+    }
+}
@@ -0,0 +1,32 @@
+use super::TuringMachine;
+
+/// Numbers on the tape for arithmetic machines. Unary writes `n` as `n` ones
+/// and binary as its base-2 digits, most significant first, with no blanks on
+/// either side; both start the machine on the first digit. Reading ignores
+/// leading and trailing blanks and fails on any other stray symbol.
+impl TuringMachine {
+    pub fn set_unary(&mut self, n: u64) {
+        self.set_tape(&"1".repeat(n as usize))
+            .expect("a unary tape is a valid tape");
+    }
+
+    /// Zero is an empty (all-blank) tape.
+    pub fn read_unary(&self) -> Option<u64> {
+        let digits = self.trimmed_tape();
+        digits.iter().all(|&c| c == '1').then_some(digits.len() as u64)
+    }
+
+    pub fn set_binary(&mut self, n: u64) {
+        self.set_tape(&format!("{n:b}"))
+            .expect("a binary tape is a valid tape");
+    }
+
+    /// None for an empty tape or a number that does not fit in a u64.
+    pub fn read_binary(&self) -> Option<u64> {
+        let digits: String = self.trimmed_tape().iter().collect();
+        if digits.is_empty() || !digits.chars().all(|c| c == '0' || c == '1') {
+            return None;
+        }
+        u64::from_str_radix(&digits, 2).ok()
+    }
+}
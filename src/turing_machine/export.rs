@@ -0,0 +1,144 @@
+use std::fmt::Write;
+
+use super::{Direction, TuringMachine, HEAD_MARKER};
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+impl TuringMachine {
+    /// Graphviz state diagram, e.g. for `dot -Tpng`. Halt states are drawn as
+    /// double circles and the start state gets an arrow from a hidden node.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph tm {\n    rankdir=LR;\n    node [shape=circle];\n");
+
+        writeln!(dot, "    __start [shape=none, label=\"\"];").unwrap();
+        writeln!(dot, "    __start -> {};", quote(&self.initial_state)).unwrap();
+
+        for state in &self.halt_states {
+            writeln!(dot, "    {} [shape=doublecircle];", quote(state)).unwrap();
+        }
+
+        for instruction in self.sorted_instructions() {
+            let label = format!(
+                "{}/{},{}",
+                self.symbol_name(instruction.current_symbol),
+                self.symbol_name(instruction.new_symbol),
+                Direction::dir2str(&instruction.direction),
+            );
+
+            writeln!(
+                dot,
+                "    {} -> {} [label={}];",
+                quote(&instruction.current_state),
+                quote(&instruction.new_state),
+                quote(&label),
+            ).unwrap();
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// One row per instruction, sorted by state then read symbol, followed by
+    /// a caption listing the halt states.
+    pub fn to_markdown_table(&self) -> String {
+        let rows: Vec<[String; 5]> = self
+            .sorted_instructions()
+            .into_iter()
+            .map(|instruction| [
+                instruction.current_state.clone(),
+                self.symbol_name(instruction.current_symbol),
+                self.symbol_name(instruction.new_symbol),
+                Direction::dir2str(&instruction.direction),
+                instruction.new_state.clone(),
+            ])
+            .collect();
+
+        let mut table = String::from(
+            "| State | Read | Write | Move | Next |\n| --- | --- | --- | --- | --- |\n"
+        );
+
+        for row in rows {
+            writeln!(
+                table,
+                "| {} |",
+                row.iter().map(|text| cell(text)).collect::<Vec<_>>().join(" | "),
+            ).unwrap();
+        }
+
+        let halt_states: Vec<&str> = self.get_halt_states().collect();
+        writeln!(table, "\nHalt states: {}", cell(&halt_states.join(", "))).unwrap();
+
+        table
+    }
+
+    /// The config `save_cfg` writes, which `load_cfg_str` reads back into an
+    /// equal machine.
+    pub fn to_cfg_string(&self) -> String {
+        let mut cfg = String::new();
+
+        if let Some(name) = &self.name {
+            writeln!(cfg, "name {name}").unwrap();
+        }
+        if self.nondeterministic {
+            writeln!(cfg, "nondeterministic").unwrap();
+        }
+        if let Some(declared) = &self.declared_states {
+            let states: Vec<&str> = declared.iter().map(String::as_str).collect();
+            writeln!(cfg, "states {}", states.join(" ")).unwrap();
+        }
+        if self.initial_tape.iter().any(|&c| self.symbol_name(c).chars().count() > 1) {
+            let tape: Vec<String> = self.initial_tape
+                .iter()
+                .map(|&c| self.symbol_name(c))
+                .collect();
+
+            writeln!(cfg, "start {}", self.initial_state).unwrap();
+            writeln!(cfg, "tape {}", tape.join(" ")).unwrap();
+            if self.initial_tape_cell > 0 {
+                writeln!(cfg, "head {}", self.initial_tape_cell).unwrap();
+            }
+        } else if self.initial_tape.is_empty() {
+            writeln!(cfg, "start {}", self.initial_state).unwrap();
+        } else {
+            let mut tape: String = self.initial_tape.iter().collect();
+            if self.initial_tape_cell > 0 {
+                let at = tape
+                    .char_indices()
+                    .nth(self.initial_tape_cell)
+                    .map_or(tape.len(), |(i, _)| i);
+                tape.insert(at, HEAD_MARKER);
+            }
+            writeln!(cfg, "{} {tape}", self.initial_state).unwrap();
+        }
+        for state in &self.halt_states {
+            if self.accept_states.contains(state) {
+                writeln!(cfg, "accept {state}").unwrap();
+            } else if self.reject_states.contains(state) {
+                writeln!(cfg, "reject {state}").unwrap();
+            } else {
+                writeln!(cfg, "{state}").unwrap();
+            }
+        }
+        writeln!(cfg, "blank {}", self.symbol_name(self.blank)).unwrap();
+
+        for instruction in self.sorted_instructions() {
+            writeln!(
+                cfg,
+                "{} {} {} {} {}",
+                instruction.current_state,
+                self.symbol_name(instruction.current_symbol),
+                instruction.new_state,
+                self.symbol_name(instruction.new_symbol),
+                Direction::dir2str(&instruction.direction),
+            ).unwrap();
+        }
+
+        cfg
+    }
+}
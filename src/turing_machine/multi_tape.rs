@@ -0,0 +1,415 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs::File,
+    io::{self, BufRead},
+};
+
+use super::{
+    apply_shared_directive, check_duplicate, check_state_name, config_fields, invalid_line,
+    numbered_lines, run_to_halt, shift, strip_comment, tape_cells, Cells, ConfigTarget, Direction,
+    Outcome, StepOutcome, Stepper, TapeMode, TmError, DEFAULT_BLANK, WILDCARD,
+};
+
+/// One tape of a multi-tape machine with its own head. Like the single tape it
+/// grows with blanks in both directions; `origin` is the index of input cell 0.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tape {
+    cells: Cells,
+    head: usize,
+    origin: usize,
+}
+
+impl Tape {
+    fn new(cells: Cells) -> Self {
+        Self { cells, head: 0, origin: 0 }
+    }
+
+    pub fn cells(&self) -> &[char] {
+        &self.cells
+    }
+
+    pub fn head_offset(&self) -> isize {
+        self.head as isize - self.origin as isize
+    }
+
+    fn read(&self, blank: char) -> char {
+        self.cells.get(self.head).copied().unwrap_or(blank)
+    }
+
+    fn write(&mut self, symbol: char, blank: char) {
+        if self.head >= self.cells.len() {
+            self.cells.resize(self.head + 1, blank);
+        }
+        self.cells[self.head] = symbol;
+    }
+
+    fn shift(&mut self, blank: char, dir: Direction) {
+        shift(&mut self.cells, &mut self.head, &mut self.origin, blank, dir, TapeMode::Infinite);
+    }
+}
+
+/// A rule reads one symbol per tape and writes and moves every tape at once,
+/// e.g. `q0 1,_ q0 1,1 right,right`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiTapeInstruction {
+    current_state: String,
+    current_symbols: Vec<char>,
+    new_state: String,
+    new_symbols: Vec<char>,
+    directions: Vec<Direction>,
+}
+
+impl MultiTapeInstruction {
+    pub fn current_state(&self) -> &str {
+        &self.current_state
+    }
+
+    pub fn current_symbols(&self) -> &[char] {
+        &self.current_symbols
+    }
+
+    pub fn new_state(&self) -> &str {
+        &self.new_state
+    }
+
+    pub fn new_symbols(&self) -> &[char] {
+        &self.new_symbols
+    }
+
+    pub fn directions(&self) -> &[Direction] {
+        &self.directions
+    }
+
+    /// `*` matches any symbol on its tape, but only when no rule matches the
+    /// symbols under the heads exactly.
+    fn matches(&self, state: &str, symbols: &[char]) -> bool {
+        self.current_state == state
+            && self.current_symbols
+                .iter()
+                .zip(symbols)
+                .all(|(&rule, &read)| rule == read || rule == WILDCARD)
+    }
+}
+
+/// A machine with `tape_count` tapes, loaded from the same config format as
+/// `TuringMachine` with tuples in place of symbols and directions:
+///
+/// ```text
+/// tape_count 2
+/// q0 110,
+/// q0 1,_ q0 1,1 right,right
+/// ```
+///
+/// The start line lists the input of every tape separated by commas. Halt,
+/// `accept`, `reject`, `name` and `blank` lines and arrow rules such as
+/// `q0,1,_ -> q0,1,1,R,R` work as for a single tape.
+#[derive(Clone, Debug)]
+pub struct MultiTapeMachine {
+    name: Option<String>,
+    state: String,
+    halt_states: BTreeSet<String>,
+    accept_states: BTreeSet<String>,
+    reject_states: BTreeSet<String>,
+    tapes: Vec<Tape>,
+    blank: char,
+    steps: usize,
+    instructions: Vec<MultiTapeInstruction>,
+    index: HashMap<(String, Vec<char>), usize>,
+}
+
+impl Default for MultiTapeMachine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MultiTapeMachine {
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            state: String::new(),
+            halt_states: BTreeSet::new(),
+            accept_states: BTreeSet::new(),
+            reject_states: BTreeSet::new(),
+            tapes: vec![Tape::default()],
+            blank: DEFAULT_BLANK,
+            steps: 0,
+            instructions: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn get_state(&self) -> &str {
+        &self.state
+    }
+
+    pub fn tape_count(&self) -> usize {
+        self.tapes.len()
+    }
+
+    pub fn tapes(&self) -> &[Tape] {
+        &self.tapes
+    }
+
+    pub fn instructions(&self) -> &[MultiTapeInstruction] {
+        &self.instructions
+    }
+
+    pub fn get_blank(&self) -> char {
+        self.blank
+    }
+
+    pub fn get_step_count(&self) -> usize {
+        self.steps
+    }
+
+    pub fn is_halt(&self) -> bool {
+        self.halt_states.contains(&self.state)
+    }
+
+    /// Decided by accept and reject states as for a single tape.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if !self.is_halt() {
+            None
+        } else if self.reject_states.contains(&self.state) {
+            Some(Outcome::Reject)
+        } else {
+            Some(Outcome::Accept)
+        }
+    }
+
+    pub fn load_cfg(&mut self, path: &str) -> Result<(), TmError> {
+        let file = File::open(path).map_err(|e| {
+            io::Error::new(e.kind(), format!("failed to open {path}: {e}"))
+        })?;
+
+        self.load_cfg_reader(io::BufReader::new(file))
+    }
+
+    pub fn load_cfg_str(&mut self, cfg: &str) -> Result<(), TmError> {
+        self.load_cfg_reader(cfg.as_bytes())
+    }
+
+    pub fn load_cfg_reader<R: BufRead>(&mut self, reader: R) -> Result<(), TmError> {
+        let lines = reader.lines().collect::<Result<Vec<_>, _>>()?;
+        let mut has_start = false;
+        let mut rule_lines: HashMap<(String, String), usize> = HashMap::new();
+
+        for (number, line) in numbered_lines(lines.iter().map(String::as_str)) {
+            let line = strip_comment(line, self.tapes.len()).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let substrings = config_fields(number, line, self.tapes.len())?;
+            if apply_shared_directive(self, number, line, &substrings)? {
+                continue;
+            }
+            let count = substrings.len();
+
+            match count {
+                2 if substrings[0] == "tape_count" => {
+                    let tapes = substrings[1]
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| invalid_line(number, line, "expected a positive tape count"))?;
+                    if has_start || !self.instructions.is_empty() {
+                        return Err(invalid_line(
+                            number,
+                            line,
+                            "tape_count must come before the start line and rules",
+                        ));
+                    }
+                    self.tapes = vec![Tape::default(); tapes];
+                }
+                2 => {
+                    check_state_name(number, line, substrings[0])?;
+                    let inputs: Vec<&str> = substrings[1].split(',').collect();
+                    self.check_arity(inputs.len(), number, line)?;
+                    self.state = substrings[0].to_string();
+                    self.tapes = inputs
+                        .iter()
+                        .map(|input| tape_cells(input).into_iter().map(single_symbol).collect())
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(|| {
+                            invalid_line(number, line, "expected single-character symbols")
+                        })?
+                        .into_iter()
+                        .map(Tape::new)
+                        .collect();
+                    has_start = true;
+                }
+                1 => check_state_name(number, line, substrings[0])?,
+                5 => {
+                    check_state_name(number, line, substrings[0])?;
+                    check_state_name(number, line, substrings[2])?;
+                    let current_symbols = self.symbols(substrings[1], number, line)?;
+                    let new_symbols = self.symbols(substrings[3], number, line)?;
+                    let directions = substrings[4]
+                        .split(',')
+                        .map(|direction| {
+                            let direction = direction.trim();
+                            Direction::str2dir(direction).ok_or_else(|| TmError::UnknownDirection {
+                                line: number,
+                                direction: direction.to_string(),
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    self.check_arity(directions.len(), number, line)?;
+
+                    let names = join_symbols(&current_symbols);
+                    check_duplicate(&mut rule_lines, substrings[0], &names, number)?;
+                    self.index.insert(
+                        (substrings[0].to_string(), current_symbols.clone()),
+                        self.instructions.len(),
+                    );
+
+                    self.instructions.push(MultiTapeInstruction {
+                        current_state: substrings[0].to_string(),
+                        current_symbols,
+                        new_state: substrings[2].to_string(),
+                        new_symbols,
+                        directions,
+                    });
+                }
+                _ => {
+                    return Err(invalid_line(
+                        number,
+                        line,
+                        &format!("expected 1, 2, or 5 fields, found {count}"),
+                    ));
+                }
+            }
+        }
+
+        if !has_start {
+            return Err(TmError::MissingStartConfig);
+        }
+
+        self.steps = 0;
+
+        Ok(())
+    }
+
+    fn check_arity(&self, count: usize, number: usize, line: &str) -> Result<(), TmError> {
+        if count != self.tapes.len() {
+            return Err(invalid_line(
+                number,
+                line,
+                &format!("expected {} comma-separated entries, found {count}", self.tapes.len()),
+            ));
+        }
+        Ok(())
+    }
+
+    fn symbols(&self, field: &str, number: usize, line: &str) -> Result<Vec<char>, TmError> {
+        let symbols = field
+            .split(',')
+            .map(|symbol| single_symbol(symbol.trim()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| invalid_line(number, line, "expected single-character symbols"))?;
+        self.check_arity(symbols.len(), number, line)?;
+        Ok(symbols)
+    }
+
+    fn find_transition(&self) -> Option<usize> {
+        let symbols: Vec<char> = self.tapes.iter().map(|t| t.read(self.blank)).collect();
+
+        self.index
+            .get(&(self.state.clone(), symbols.clone()))
+            .copied()
+            .or_else(|| self.instructions.iter().position(|i| i.matches(&self.state, &symbols)))
+    }
+
+    pub fn step(&mut self) -> StepOutcome {
+        if self.is_halt() {
+            return StepOutcome::Halted;
+        }
+
+        let Some(i) = self.find_transition() else {
+            return StepOutcome::NoTransition;
+        };
+
+        let instruction = &self.instructions[i];
+        for (t, tape) in self.tapes.iter_mut().enumerate() {
+            let symbol = match instruction.new_symbols[t] {
+                WILDCARD => tape.read(self.blank),
+                symbol => symbol,
+            };
+            tape.write(symbol, self.blank);
+            tape.shift(self.blank, instruction.directions[t]);
+        }
+        self.state = instruction.new_state.clone();
+        self.steps += 1;
+
+        StepOutcome::Applied
+    }
+
+    pub fn run(&mut self, max_steps: usize) -> Result<usize, TmError> {
+        run_to_halt(self, max_steps)
+    }
+}
+
+impl ConfigTarget for MultiTapeMachine {
+    fn set_blank(&mut self, name: &str) -> Result<(), String> {
+        self.blank = single_symbol(name)
+            .ok_or_else(|| format!("the blank must be a single tape cell, found {name:?}"))?;
+        Ok(())
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+
+    fn add_halt(&mut self, state: &str) {
+        self.halt_states.insert(state.to_string());
+    }
+
+    fn add_accept(&mut self, state: &str) {
+        self.add_halt(state);
+        self.accept_states.insert(state.to_string());
+    }
+
+    fn add_reject(&mut self, state: &str) {
+        self.add_halt(state);
+        self.reject_states.insert(state.to_string());
+    }
+}
+
+impl Stepper for MultiTapeMachine {
+    fn step(&mut self) -> StepOutcome {
+        MultiTapeMachine::step(self)
+    }
+
+    fn is_halt(&self) -> bool {
+        MultiTapeMachine::is_halt(self)
+    }
+
+    /// A multi-tape machine can only get stuck; the error lists the symbol
+    /// under every head, e.g. `1,_`.
+    fn stop_error(&self, _: StepOutcome) -> TmError {
+        let symbols: Vec<char> = self.tapes.iter().map(|t| t.read(self.blank)).collect();
+        TmError::NoTransition {
+            state: self.state.clone(),
+            symbol: join_symbols(&symbols),
+        }
+    }
+}
+
+/// Symbols per tape as a rule writes them, e.g. `1,_`.
+fn join_symbols(symbols: &[char]) -> String {
+    symbols.iter().map(char::to_string).collect::<Vec<_>>().join(",")
+}
+
+fn single_symbol(token: &str) -> Option<char> {
+    let mut chars = token.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::load;
+use tms::turing_machine::{TmError, TuringMachine, WarningKind};
+
+fn validation_error(cfg: &str) -> String {
+    match load(cfg).validate() {
+        Err(TmError::Validation(message)) => message,
+        result => panic!("unexpected result {result:?}"),
+    }
+}
+
+#[test]
+fn valid_machine_passes() {
+    assert!(load("q0 1\nqf\nq0 1 qf 1 right\n").validate().is_ok());
+    assert!(load("qf 1\nqf\n").validate().is_ok());
+}
+
+#[test]
+fn start_state_without_rules_fails() {
+    assert_eq!(
+        validation_error("q1 1\nqf\nq0 1 qf 1 right\n"),
+        "start state q1 has no outgoing instruction",
+    );
+}
+
+#[test]
+fn halt_state_with_rules_fails() {
+    assert_eq!(
+        validation_error("q0 1\nqf\nq0 1 qf 1 right\nqf 1 q0 1 left\n"),
+        "halt state qf has outgoing instructions",
+    );
+}
+
+#[test]
+fn dead_end_state_fails() {
+    assert_eq!(
+        validation_error("q0 1\nqf\nq0 1 q1 1 right\n"),
+        "state q1 is neither a halt state nor has outgoing instructions",
+    );
+}
+
+const FLIP: &str = "qf\nq0 0 q0 1 right\nq0 1 q0 0 right\nq0 _ qf _ stay\n";
+
+#[test]
+fn input_over_the_machine_alphabet_is_valid() {
+    assert!(load(&format!("q0 0110_1\n{FLIP}")).validate_input().is_ok());
+}
+
+#[test]
+fn stray_input_symbol_is_reported_with_its_cell() {
+    match load(&format!("q0 01x0\n{FLIP}")).validate_input() {
+        Err(TmError::UnexpectedSymbol { symbol, cell }) => {
+            assert_eq!(symbol, "x");
+            assert_eq!(cell, 2);
+        }
+        result => panic!("unexpected result {result:?}"),
+    }
+}
+
+#[test]
+fn sloppy_machine_gets_every_warning() {
+    let tm = load("\
+q0 1
+qf
+q0 1 q1 1 right
+q1 x qf 1 stay
+q1 _ q2 _ stay
+qf 1 qf 1 stay
+q9 1 qf 1 stay
+");
+
+    let warnings: Vec<_> = tm.warnings().into_iter().map(|w| (w.kind, w.message)).collect();
+    assert_eq!(warnings, [
+        (WarningKind::NoOutgoingRule, "state q2 has no outgoing instruction".to_string()),
+        (WarningKind::UnreachableState, "state q9 is unreachable".to_string()),
+        (WarningKind::HaltStateAsSource, "halt state qf has outgoing instructions".to_string()),
+        (WarningKind::SymbolNeverWritten, "symbol \"x\" is read but never written".to_string()),
+    ]);
+}
+
+#[test]
+fn check_cfg_lists_no_warnings_for_a_clean_machine() {
+    assert!(TuringMachine::check_cfg("examples/odd_or_even.cfg").unwrap().is_empty());
+    assert!(matches!(TuringMachine::check_cfg("does/not/exist.cfg"), Err(TmError::Io(_))));
+}
+
+#[test]
+fn deterministic_total_machine() {
+    let tm = load("q0 01\nqf\nq0 0 q0 1 right\nq0 1 q0 0 right\nq0 _ qf _ stay\n");
+
+    assert!(tm.is_deterministic());
+    assert!(tm.is_total());
+}
+
+#[test]
+fn machine_repeating_a_rule_key_is_nondeterministic() {
+    let mut tm = TuringMachine::new();
+    tm.set_nondeterministic(true);
+    tm.load_cfg_str("q0 0\nqf\nq0 0 q0 0 right\nq0 0 qf 0 stay\nq0 * qf * stay\n").unwrap();
+
+    assert!(!tm.is_deterministic());
+    assert!(tm.is_total());
+}
+
+#[test]
+fn machine_missing_a_rule_is_partial() {
+    let tm = load("q0 01\nqf\nq0 0 q0 1 right\nq0 _ qf _ stay\n");
+
+    assert!(tm.is_deterministic());
+    assert!(!tm.is_total());
+}
@@ -0,0 +1,30 @@
+//! Loaders and machines shared by the integration tests. Each test binary
+//! uses only some of them.
+#![allow(dead_code)]
+
+use tms::turing_machine::{TmError, TuringMachine};
+
+pub fn load(cfg: &str) -> TuringMachine {
+    let mut tm = TuringMachine::new();
+    tm.load_cfg_str(cfg).unwrap();
+    tm
+}
+
+pub fn load_err(cfg: &str) -> TmError {
+    TuringMachine::new().load_cfg_str(cfg).unwrap_err()
+}
+
+/// Adds one to a binary number, starting on its last digit.
+pub const INCREMENT: &str = "\
+q0 101@1
+qf
+q0 1 q0 0 left
+q0 0 qf 1 stay
+q0 _ qf 1 stay
+";
+
+/// Walks right forever.
+pub const LOOP: &str = "q0 1\nqf\nq0 1 q0 1 right\nq0 _ q0 _ right\n";
+
+/// Flips every bit of its input, halting on the first blank.
+pub const FLIP: &str = "q0 0110\nqf\nq0 0 q0 1 right\nq0 1 q0 0 right\nq0 _ qf _ stay\n";
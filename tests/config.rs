@@ -0,0 +1,429 @@
+mod common;
+
+use std::{fs, io::Cursor};
+
+use common::{load, load_err};
+use tms::turing_machine::{TmError, TuringMachine};
+
+#[test]
+fn missing_config_file_is_an_error() {
+    let mut tm = TuringMachine::new();
+    let err = tm.load_cfg("does/not/exist.cfg").unwrap_err();
+
+    assert!(err.to_string().starts_with("failed to open does/not/exist.cfg: "), "{err}");
+}
+
+#[test]
+fn trailing_space_does_not_make_an_empty_field() {
+    let tm = load("q0 1 \nqf \nq0 1 qf 0 right \n");
+
+    assert_eq!(tm.instructions().len(), 1);
+    assert_eq!(tm.instructions()[0].new_symbol(), '0');
+}
+
+#[test]
+fn empty_symbol_field_is_rejected() {
+    let err = load_err("q0 1\nqf\nq0, -> qf,0,right\n");
+
+    assert_eq!(
+        err.to_string(),
+        "line 3: expected <state>,<symbol> -> <new_state>,<new_symbol>,<direction>: \"q0, -> qf,0,right\"",
+    );
+}
+
+#[test]
+fn arrow_rules_load_the_same_machine_as_space_separated_ones() {
+    let spaces = load("q0 10\nqf\nq0 1 q0 0 right\nq0 0 q1 1 R\nq1 _ qf _ stay\n");
+    let arrows = load("q0 10\nqf\nq0,1 -> q0,0,right\nq0, 0 -> q1, 1, R\nq1,_->qf,_,stay\n");
+
+    assert_eq!(spaces, arrows);
+    assert!(spaces.instructions() == arrows.instructions());
+}
+
+#[test]
+fn malformed_arrow_rule_is_rejected() {
+    let rules = [
+        "q0 1 -> q0 0 right",
+        "q0,1 -> q0,0",
+        "q0,1 -> q0,0,right,left",
+        "q0,1,2 -> q0,0,R",
+    ];
+    for rule in rules {
+        let err = load_err(&format!("q0 1\nqf\n{rule}\n"));
+        assert!(matches!(err, TmError::Parse { line: 3, .. }), "{rule}: {err:?}");
+    }
+}
+
+#[test]
+fn multi_character_symbol_is_one_symbol() {
+    let tm = load("start q0\ntape ab\nqf\nq0 ab qf cd right\n");
+    let instruction = &tm.instructions()[0];
+
+    assert_eq!(tm.symbol_name(instruction.current_symbol()), "ab");
+    assert_eq!(tm.symbol_name(instruction.new_symbol()), "cd");
+    assert_eq!(tm.get_tape(), &[instruction.current_symbol()]);
+}
+
+#[test]
+fn private_use_characters_are_rejected() {
+    for cfg in [
+        "q0 a\u{e000}\nqf\n",
+        "start q0\ntape ab \u{e000}\nqf\n",
+        "q0 a\nqf\nq0 \u{e000} qf a stay\n",
+        "q0 a\nqf\nq0 a qf x\u{e001} stay\n",
+        "q0 a\nblank \u{f8ff}\nqf\n",
+    ] {
+        let err = load_err(cfg);
+        assert!(matches!(err, TmError::Parse { .. }), "{cfg:?}: {err:?}");
+        assert!(err.to_string().contains("private use character"), "{err}");
+    }
+
+    let mut tm = load("start q0\ntape ab\nqf\n");
+    assert!(tm.set_tape("\u{e000}").is_err());
+}
+
+#[test]
+fn misspelled_direction_is_rejected() {
+    let err = load_err("q0 1\nqf\nq0 1 qf 0 rihgt\n");
+
+    assert!(matches!(err, TmError::UnknownDirection { .. }), "{err:?}");
+    assert_eq!(
+        err.to_string(),
+        "line 3: invalid direction \"rihgt\", expected left, right or stay",
+    );
+}
+
+#[test]
+fn field_count_error_names_the_line() {
+    let err = load_err("# header\n\nq0 1\nqf\nq0 1 qf\n");
+
+    assert_eq!(err.to_string(), "line 5: expected 1, 2, or 5 fields, found 3: \"q0 1 qf\"");
+}
+
+#[test]
+fn bad_direction_error_names_the_line() {
+    let err = load_err("q0 1\n\nqf\nq0 1 qf 0 up\n");
+
+    assert!(matches!(err, TmError::UnknownDirection { line: 4, .. }), "{err:?}");
+}
+
+#[test]
+fn empty_symbol_error_names_the_line() {
+    let err = load_err("q0 1\nqf\n\n\nq0,1 -> qf,,right\n");
+
+    assert!(matches!(err, TmError::Parse { line: 5, .. }), "{err:?}");
+}
+
+#[test]
+fn file_and_reader_load_the_same_machine() {
+    let path = "examples/odd_or_even.cfg";
+    let mut from_file = TuringMachine::new();
+    from_file.load_cfg(path).unwrap();
+
+    let text = fs::read_to_string(path).unwrap();
+    let mut from_reader = TuringMachine::new();
+    from_reader.load_cfg_reader(Cursor::new(text.as_bytes())).unwrap();
+
+    assert_eq!(from_file, from_reader);
+    assert_eq!(from_file.to_string(), from_reader.to_string());
+    assert!(from_file.instructions() == from_reader.instructions());
+    assert_eq!(from_file.get_state(), "q0");
+    assert_eq!(from_file.instructions().len(), 4);
+}
+
+#[test]
+fn byte_slice_reader_loads_like_stdin() {
+    let mut tm = TuringMachine::new();
+    tm.load_cfg_reader(&b"q0 11\nqf\nq0 1 q0 0 right\nq0 _ qf _ stay\n"[..]).unwrap();
+
+    assert_eq!(tm.run(10).unwrap(), 3);
+    assert_eq!(tm.trimmed_tape(), &['0', '0']);
+
+    let err = TuringMachine::new().load_cfg_reader(&b"q0 1\nqf\nq0 1 qf\n"[..]).unwrap_err();
+    assert!(matches!(err, TmError::Parse { line: 3, .. }), "{err:?}");
+}
+
+#[test]
+fn inline_config_parses_and_runs() {
+    let mut tm = load("
+        # walks to the end of a run of ones
+        q0 111
+        qf
+
+        q0 1 q0 1 right
+        q0 _ qf _ stay
+    ");
+
+    assert_eq!(tm.run(10).unwrap(), 4);
+    assert_eq!(tm.get_tape_cell(), 3);
+}
+
+#[test]
+fn windows_line_endings_and_trailing_newline_parse() {
+    let mut tm = load("q0 1\r\nqf\r\nq0 1 qf 0 right\r\n");
+
+    assert_eq!(tm.run(10).unwrap(), 1);
+    assert_eq!(tm.get_tape(), &['0', '_']);
+}
+
+#[test]
+fn duplicate_rules_name_both_lines() {
+    let err = load_err("q0 1\nqf\nq0 1 qf 1 right\nq0 0 qf 0 right\nq0 1 qf 0 left\n");
+
+    match err {
+        TmError::DuplicateInstruction { state, first_line, second_line, .. } => {
+            assert_eq!(state, "q0");
+            assert_eq!((first_line, second_line), (3, 5));
+        }
+        err => panic!("unexpected error {err:?}"),
+    }
+}
+
+#[test]
+fn nondeterministic_machines_may_repeat_a_rule_key() {
+    let mut tm = TuringMachine::new();
+    tm.set_nondeterministic(true);
+    tm.load_cfg_str("q0 1\nqf\nq0 1 qf 1 right\nq0 1 qf 0 left\n").unwrap();
+
+    assert_eq!(tm.instructions().len(), 2);
+}
+
+#[test]
+fn multi_character_blank_is_rejected() {
+    let err = load_err("blank b1\nq0 1\nqf\n");
+
+    assert_eq!(
+        err.to_string(),
+        "line 1: the blank must be a single tape cell, found \"b1\": \"blank b1\"",
+    );
+}
+
+#[test]
+fn reserved_words_cannot_name_states() {
+    let words = [
+        "start", "tape", "blank", "accept", "reject",
+        "name", "machine", "head", "states", "strict", "tape_count", "nondeterministic",
+    ];
+    for word in words {
+        let err = load_err(&format!("q0 1\nqf\nq0 1 {word} 1 right\n"));
+
+        assert!(matches!(err, TmError::Parse { line: 3, .. }), "{word}: {err:?}");
+        assert!(err.to_string().contains("reserved word"), "{err}");
+    }
+
+    let err = load_err("q0 1\nhead\n");
+    assert!(matches!(err, TmError::Parse { line: 2, .. }), "{err:?}");
+}
+
+#[test]
+fn duplicate_rules_name_a_multi_character_symbol() {
+    let err = load_err("start q0\ntape a1\nqf\nq0 a1 qf b2 stay\nq0 a1 qf a1 stay\n");
+
+    match err {
+        TmError::DuplicateInstruction { symbol, .. } => assert_eq!(symbol, "a1"),
+        err => panic!("unexpected error {err:?}"),
+    }
+}
+
+#[test]
+fn head_marker_sets_the_start_cell() {
+    let tm = load("q0 00@110\nqf\n");
+
+    assert_eq!(tm.get_tape(), &['0', '0', '1', '1', '0']);
+    assert_eq!(tm.get_tape_cell(), 2);
+    assert_eq!(tm.head_symbol(), '1');
+}
+
+#[test]
+fn head_marker_past_the_input_reads_the_configured_blank() {
+    let cfg = "q0 ab@\nblank x\nqf\nq0 x qf y stay\n";
+    let mut tm = load(cfg);
+
+    assert_eq!(tm.get_tape(), &['a', 'b', 'x']);
+    assert_eq!(tm.get_tape_cell(), 2);
+    assert_eq!(tm.run(10).unwrap(), 1);
+    assert_eq!(tm.get_tape(), &['a', 'b', 'y']);
+
+    let reloaded = load(&load(cfg).to_cfg_string());
+    assert_eq!(reloaded, load(cfg));
+}
+
+#[test]
+fn two_head_markers_are_rejected() {
+    let err = load_err("q0 0@1@0\nqf\n");
+
+    assert_eq!(
+        err.to_string(),
+        "line 1: expected at most one '@' head marker, found 2: \"q0 0@1@0\"",
+    );
+}
+
+#[test]
+fn trailing_comments_after_rules_are_ignored() {
+    let tm = load("\
+q0 1a#
+qf
+q0 1 q0 0 right # flip
+q0 a# qf a# stay // keep
+q0,a -> q0,a,right # arrow
+");
+
+    assert_eq!(tm.get_tape(), &['1', 'a', '#']);
+    assert_eq!(tm.get_halt_states().collect::<Vec<_>>(), ["qf"]);
+    assert_eq!(tm.instructions().len(), 3);
+    assert_eq!(tm.instructions()[1].current_symbol_name(&tm), "a#");
+}
+
+#[test]
+fn hash_symbols_are_not_comments() {
+    let tm = load("q0 #1#\nqf\nq0 # q0 // right\nq0 1 q0 # right\n");
+
+    assert_eq!(tm.get_tape(), &['#', '1', '#']);
+    assert_eq!(tm.instructions().len(), 2);
+    assert_eq!(tm.instructions()[0].current_symbol(), '#');
+    assert_eq!(tm.instructions()[0].new_symbol_name(&tm), "//");
+    assert_eq!(tm.instructions()[1].new_symbol(), '#');
+}
+
+#[test]
+fn comment_after_a_halt_state_is_not_stripped() {
+    let err = load_err("q0 1\nqf # the only halt state\n");
+    assert!(matches!(err, TmError::Parse { line: 2, .. }), "{err:?}");
+
+    let err = load_err("q0 1\nqf\nq0 1 #q 1 right\n");
+    assert!(err.to_string().contains("starts like a comment"), "{err}");
+}
+
+#[test]
+fn load_all_reads_each_named_machine() {
+    let path = std::env::temp_dir().join(format!("tms-{}-load-all.cfg", std::process::id()));
+    fs::write(&path, "\
+machine flip
+q0 01
+qf
+q0 0 q0 1 right
+q0 1 q0 0 right
+q0 _ qf _ stay
+
+machine erase
+q0 11
+qf
+q0 1 q0 _ right
+q0 _ qf _ stay
+").unwrap();
+    let machines = TuringMachine::load_all(path.to_str().unwrap());
+    fs::remove_file(&path).unwrap();
+
+    let mut machines = machines.unwrap();
+    let names: Vec<_> = machines.iter().map(|tm| tm.name()).collect();
+    assert_eq!(names, [Some("flip"), Some("erase")]);
+
+    machines[0].run(10).unwrap();
+    assert_eq!(machines[0].trimmed_tape(), &['1', '0']);
+    machines[1].run(10).unwrap();
+    assert!(machines[1].trimmed_tape().is_empty());
+}
+
+#[test]
+fn mixed_line_endings_parse_like_unix_ones() {
+    let unix = load("q0 10\nqf\nq0 1 q0 0 right\nq0 0 qf 1 left\n");
+    let mixed = load("q0 10\r\nqf\rq0 1 q0 0 right\r\n\r\nq0 0 qf 1 left\r");
+
+    assert_eq!(mixed, unix);
+    assert_eq!(mixed.instructions()[0].direction(), unix.instructions()[0].direction());
+}
+
+#[test]
+fn hash_and_slash_comments_can_be_interleaved() {
+    let tm = load("\
+# a machine that flips one bit
+// written with both comment styles
+q0 1
+    # halt
+qf
+// the rule
+q0 1 qf 0 stay
+");
+
+    assert_eq!(tm, load("q0 1\nqf\nq0 1 qf 0 stay\n"));
+}
+
+#[test]
+fn tab_aligned_columns_parse_like_space_aligned_ones() {
+    let tabs = load("q0\t\t10\nqf\nq0\t1\tq0\t0\tright\nq0\t0\tqf\t1\tstay\n");
+    let spaces = load("  q0   10\n  qf\n  q0  1  q0  0  right\n  q0  0  qf  1  stay\n");
+
+    assert_eq!(tabs, spaces);
+    assert_eq!(tabs, load("q0 10\nqf\nq0 1 q0 0 right\nq0 0 qf 1 stay\n"));
+}
+
+#[test]
+fn head_directive_sets_the_start_cell() {
+    let tm = load("start q0\ntape a1 b2 c3\nhead 1\nqf\n");
+    assert_eq!(tm.get_tape_cell(), 1);
+    assert_eq!(tm.symbol_name(tm.head_symbol()), "b2");
+
+    let tm = load("q0 01\nhead 4\nqf\n");
+    assert_eq!(tm.get_tape(), &['0', '1', '_', '_', '_']);
+    assert_eq!(tm.get_tape_cell(), 4);
+}
+
+#[test]
+fn head_directive_conflicts_with_a_head_marker() {
+    let err = load_err("q0 0@1\nqf\nhead 0\n");
+
+    assert!(matches!(err, TmError::Parse { line: 3, .. }), "{err:?}");
+    assert!(err.to_string().contains("conflicts"), "{err}");
+}
+
+#[test]
+fn declared_states_allow_their_rules() {
+    let tm = load("states q0 q1 qf\nq0 1\nqf\nq0 1 q1 1 right\nq1 _ qf _ stay\n");
+
+    assert_eq!(tm.instructions().len(), 2);
+}
+
+#[test]
+fn rule_using_an_undeclared_state_is_rejected() {
+    let err = load_err("states q0 q1 qf\nq0 1\nqf\nq0 1 q01 1 right\n");
+
+    assert_eq!(err.to_string(), "line 4: undeclared state q01: \"q0 1 q01 1 right\"");
+}
+
+#[test]
+fn direction_aliases_load_like_the_long_forms() {
+    let aliases = load("q0 10\nqf\nq0 1 q1 1 R\nq1 0 q2 0 <\nq2 1 q3 1 >\nq3 0 qf 0 -\n");
+    let long = load("q0 10\nqf\nq0 1 q1 1 right\nq1 0 q2 0 left\nq2 1 q3 1 right\nq3 0 qf 0 stay\n");
+
+    assert_eq!(aliases, long);
+    assert!(aliases.to_cfg_string().contains("q1 0 q2 0 left\n"));
+}
+
+#[test]
+fn strict_config_with_every_state_declared_loads() {
+    let tm = load("strict\nq0 1\nqf\nstates q0 q1 qf\nq0 1 q1 1 right\nq1 _ qf _ stay\n");
+
+    assert_eq!(tm.instructions().len(), 2);
+}
+
+#[test]
+fn strict_config_with_an_undeclared_state_fails() {
+    // Only the halt states are declared; q0 is not.
+    let err = load_err("q0 1\nqf\nq0 1 q0 1 right\nq0 _ qf _ stay\nstrict\n");
+
+    assert!(matches!(err, TmError::Parse { line: 3, .. }), "{err:?}");
+    assert!(err.to_string().contains("state q0 is not declared (strict mode)"), "{err}");
+    assert_eq!(load("q0 1\nqf\nq0 1 q0 1 right\nq0 _ qf _ stay\n").instructions().len(), 2);
+}
+
+#[test]
+fn nondeterministic_line_allows_duplicate_rules_anywhere_in_the_file() {
+    let rules = "q0 1\nqf\nq0 1 q0 1 right\nq0 1 qf 1 stay\n";
+
+    let tm = load(&format!("{rules}nondeterministic\n"));
+    assert_eq!(tm.get_instructions_for("q0", '1').len(), 2);
+    assert_eq!(load(&format!("nondeterministic\n{rules}")), tm);
+
+    let err = load_err(rules);
+    assert!(matches!(err, TmError::DuplicateInstruction { second_line: 4, .. }), "{err:?}");
+}
@@ -0,0 +1,39 @@
+use tms::turing_machine::Direction;
+
+#[test]
+fn directions_display_as_their_long_form() {
+    assert_eq!(Direction::Lhs.to_string(), "left");
+    assert_eq!(Direction::Rhs.to_string(), "right");
+    assert_eq!(Direction::Stay.to_string(), "stay");
+}
+
+#[test]
+fn displayed_directions_parse_back() {
+    for dir in [Direction::Lhs, Direction::Rhs, Direction::Stay] {
+        assert_eq!(dir.to_string().parse::<Direction>().unwrap(), dir);
+    }
+}
+
+#[test]
+fn short_forms_and_any_case_parse() {
+    for (text, dir) in [
+        ("LEFT", Direction::Lhs),
+        ("l", Direction::Lhs),
+        ("<", Direction::Lhs),
+        ("Right", Direction::Rhs),
+        ("R", Direction::Rhs),
+        (">", Direction::Rhs),
+        ("sTaY", Direction::Stay),
+        ("s", Direction::Stay),
+        ("-", Direction::Stay),
+    ] {
+        assert_eq!(text.parse::<Direction>().unwrap(), dir, "{text}");
+    }
+}
+
+#[test]
+fn unknown_direction_is_a_descriptive_error() {
+    let err = "rihgt".parse::<Direction>().unwrap_err();
+
+    assert_eq!(err.to_string(), "invalid direction \"rihgt\", expected left, right or stay");
+}
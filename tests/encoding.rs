@@ -0,0 +1,46 @@
+mod common;
+
+use common::load;
+
+#[test]
+fn unary_incrementer_adds_one() {
+    let mut tm = load("start q0\nqf\nq0 1 q0 1 right\nq0 _ qf 1 stay\n");
+
+    for n in [0, 1, 5] {
+        tm.set_unary(n);
+        assert_eq!(tm.read_unary(), Some(n));
+        tm.run(100).unwrap();
+        assert_eq!(tm.read_unary(), Some(n + 1));
+    }
+}
+
+#[test]
+fn binary_incrementer_adds_one() {
+    let mut tm = load("\
+start q0
+qf
+q0 0 q0 0 right
+q0 1 q0 1 right
+q0 _ q1 _ left
+q1 1 q1 0 left
+q1 0 qf 1 stay
+q1 _ qf 1 stay
+");
+
+    for n in [0, 7, 11, 1 << 40] {
+        tm.set_binary(n);
+        assert_eq!(tm.read_binary(), Some(n));
+        tm.run(100).unwrap();
+        assert_eq!(tm.read_binary(), Some(n + 1));
+    }
+}
+
+#[test]
+fn stray_symbols_do_not_decode() {
+    let tm = load("q0 1x1\nqf\n");
+
+    assert_eq!(tm.read_unary(), None);
+    assert_eq!(tm.read_binary(), None);
+    assert_eq!(load("q0 ___\nqf\n").read_binary(), None);
+    assert_eq!(load("q0 __11_\nqf\n").read_unary(), Some(2));
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use std::error::Error;
+
+use common::load_err;
+use tms::turing_machine::{TmError, TuringMachine};
+
+#[test]
+fn missing_file_is_an_io_error() {
+    let mut tm = TuringMachine::new();
+    let err = tm.load_cfg("does/not/exist.cfg").unwrap_err();
+
+    assert!(matches!(err, TmError::Io(_)), "{err:?}");
+    assert!(err.to_string().contains("does/not/exist.cfg"), "{err}");
+    assert!(err.source().is_some());
+}
+
+#[test]
+fn wrong_field_count_is_a_parse_error() {
+    let err = load_err("q0 1\nqf\nq0 1 qf\n");
+
+    assert!(matches!(err, TmError::Parse { line: 3, .. }), "{err:?}");
+    assert!(err.source().is_none());
+}
+
+#[test]
+fn bad_direction_is_an_unknown_direction_error() {
+    let err = load_err("q0 1\nqf\nq0 1 qf 1 up\n");
+
+    match err {
+        TmError::UnknownDirection { line, direction } => {
+            assert_eq!(line, 3);
+            assert_eq!(direction, "up");
+        }
+        err => panic!("unexpected error {err:?}"),
+    }
+}
+
+#[test]
+fn config_without_start_line_is_missing_start_config() {
+    let err = load_err("qf\nq0 1 qf 1 right\n");
+
+    assert!(matches!(err, TmError::MissingStartConfig), "{err:?}");
+}
+
+#[test]
+fn io_errors_convert_with_question_mark() {
+    fn open() -> Result<(), TmError> {
+        std::fs::File::open("does/not/exist.cfg")?;
+        Ok(())
+    }
+
+    assert!(matches!(open(), Err(TmError::Io(_))));
+}
@@ -0,0 +1,171 @@
+mod common;
+
+use std::{env, fs, process};
+
+use common::load;
+use tms::turing_machine::{Direction, TuringMachine};
+
+fn temp_path(name: &str) -> String {
+    env::temp_dir()
+        .join(format!("tms-{}-{name}.cfg", process::id()))
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn saved_config_reloads_into_an_equal_machine() {
+    let mut tm = TuringMachine::new();
+    tm.load_cfg("examples/example.cfg").unwrap();
+    let path = temp_path("save");
+
+    tm.save_cfg(&path).unwrap();
+    let mut reloaded = TuringMachine::new();
+    reloaded.load_cfg(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(reloaded, tm);
+    assert_eq!(reloaded.to_cfg_string(), tm.to_cfg_string());
+}
+
+#[test]
+fn dot_output_has_the_states_and_labelled_edges() {
+    let tm = load("q0 01\nqf\nq0 0 q0 1 right\nq0 1 qf 0 stay\n");
+
+    assert_eq!(tm.to_dot(), "\
+digraph tm {
+    rankdir=LR;
+    node [shape=circle];
+    __start [shape=none, label=\"\"];
+    __start -> \"q0\";
+    \"qf\" [shape=doublecircle];
+    \"q0\" -> \"q0\" [label=\"0/1,right\"];
+    \"q0\" -> \"qf\" [label=\"1/0,stay\"];
+}
+");
+}
+
+#[test]
+fn markdown_table_lists_sorted_rules_and_halt_states() {
+    let tm = load("q0 0\nqf\nqr\nq1 _ qf _ stay\nq0 1 q1 1 left\nq0 0 q0 0 right\n");
+
+    assert_eq!(tm.to_markdown_table(), "\
+| State | Read | Write | Move | Next |
+| --- | --- | --- | --- | --- |
+| q0 | 0 | 0 | right | q0 |
+| q0 | 1 | 1 | left | q1 |
+| q1 | _ | _ | stay | qf |
+
+Halt states: qf, qr
+");
+}
+
+#[test]
+fn name_survives_a_save_and_reload() {
+    let tm = load("name flipper\nq0 01\nqf\nq0 0 q0 1 right\n");
+    let path = temp_path("name");
+
+    tm.save_cfg(&path).unwrap();
+    let mut reloaded = TuringMachine::new();
+    reloaded.load_cfg(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(reloaded.name(), Some("flipper"));
+    assert!(reloaded.to_string().contains("flipper"), "{reloaded}");
+
+    reloaded.reset();
+    assert_eq!(reloaded.name(), None);
+}
+
+#[test]
+fn exports_sort_rules_loaded_in_any_order() {
+    let scrambled = load("\
+q0 0
+qf
+q1 1 qf 1 stay
+q0 1 q1 0 left
+q1 0 q0 1 right
+q0 0 q0 0 right
+");
+    let sorted = load("\
+q0 0
+qf
+q0 0 q0 0 right
+q0 1 q1 0 left
+q1 0 q0 1 right
+q1 1 qf 1 stay
+");
+
+    assert_eq!(scrambled.to_cfg_string(), sorted.to_cfg_string());
+    assert_eq!(scrambled.to_markdown_table(), sorted.to_markdown_table());
+    assert_eq!(scrambled.to_dot(), sorted.to_dot());
+    assert!(scrambled.to_cfg_string().ends_with("\
+q0 0 q0 0 right
+q0 1 q1 0 left
+q1 0 q0 1 right
+q1 1 qf 1 stay
+"));
+}
+
+#[test]
+fn sorting_exports_keeps_the_first_matching_rule() {
+    let mut tm = TuringMachine::new();
+    tm.set_nondeterministic(true);
+    tm.load_cfg_str("q0 0\nqa\nqb\nq0 0 qb 0 stay\nq0 0 qa 0 stay\n").unwrap();
+    let _ = tm.to_cfg_string();
+
+    tm.run(10).unwrap();
+    assert_eq!(tm.get_state(), "qb");
+}
+
+#[test]
+fn cfg_string_reloads_into_an_equal_machine() {
+    let configs = [
+        "q0 0@110\nqf\nq0 0 q0 1 right\nq0 1 qf 0 stay\n",
+        "name pairs\nstart q0\ntape a1 b2 a1\nhead 1\naccept yes\nreject no\nq0 a1 yes b2 left\n",
+        "start q0\nblank x\nqf\nq0 x qf a1 stay\n",
+        "states q0 qf\nq0 1\nqf\nq0 * qf * stay\n",
+    ];
+    for cfg in configs {
+        let tm = load(cfg);
+        let reloaded = load(&tm.to_cfg_string());
+
+        assert_eq!(reloaded, tm, "{cfg}");
+        assert_eq!(reloaded.to_cfg_string(), tm.to_cfg_string());
+    }
+}
+
+#[test]
+fn nondeterministic_machine_reloads_from_its_cfg_string() {
+    let mut tm = TuringMachine::new();
+    tm.set_nondeterministic(true);
+    tm.load_cfg_str("q0 11\nqf\nq0 1 q0 1 right\nq0 1 q1 1 right\nq1 1 qf 1 stay\n").unwrap();
+
+    let cfg = tm.to_cfg_string();
+    assert!(cfg.starts_with("nondeterministic\n"), "{cfg}");
+    let reloaded = load(&cfg);
+
+    assert_eq!(reloaded, tm);
+    assert_eq!(reloaded.get_instructions_for("q0", '1').len(), 2);
+    assert_eq!(reloaded.run_nondeterministic(10).unwrap().len(), 3);
+}
+
+#[test]
+fn hash_tapes_and_symbols_survive_a_cfg_round_trip() {
+    let mut tm = load("q0 1\nqf\nq0 # q0 // right\n");
+    tm.add_instruction("q0", 'a', "qf", '#', Direction::Stay);
+    tm.set_start("q0", "#ab").unwrap();
+
+    let reloaded = load(&tm.to_cfg_string());
+    assert_eq!(reloaded, tm);
+    assert_eq!(reloaded.get_tape(), &['#', 'a', 'b']);
+    assert_eq!(reloaded.instructions().len(), 2);
+}
+
+#[test]
+fn set_start_rejects_a_state_that_reads_as_a_comment() {
+    let mut tm = load("q0 1\nqf\n");
+
+    assert!(tm.set_start("#q", "1").is_err());
+    assert!(tm.set_start("//q", "1").is_err());
+    assert_eq!(tm.get_state(), "q0");
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "graphemes")]
+
+mod common;
+
+use common::load;
+use tms::turing_machine::{MultiTapeMachine, TmError};
+
+/// `é` written as `e` followed by a combining acute accent.
+const E_ACUTE: &str = "e\u{301}";
+
+#[test]
+fn set_tape_reads_a_combined_character_as_one_cell() {
+    let mut tm = load(&format!("q0 a\nqf\nq0 {E_ACUTE} qf x stay\n"));
+    tm.set_tape(E_ACUTE).unwrap();
+
+    assert_eq!(tm.get_tape().len(), 1);
+    assert_eq!(tm.symbol_name(tm.head_symbol()), E_ACUTE);
+    assert_eq!(tm.run(10).unwrap(), 1);
+    assert_eq!(tm.get_tape(), &['x']);
+}
+
+#[test]
+fn set_start_splits_the_tape_into_grapheme_clusters() {
+    let mut tm = load(&format!("q0 a\nqf\nq0 a q0 a right\nq0 {E_ACUTE} qf b stay\n"));
+    tm.set_start("q0", &format!("a{E_ACUTE}@{E_ACUTE}a")).unwrap();
+
+    let names: Vec<String> = tm.get_tape().iter().map(|&c| tm.symbol_name(c)).collect();
+    assert_eq!(names, ["a", E_ACUTE, E_ACUTE, "a"]);
+    assert_eq!(tm.get_tape_cell(), 2);
+
+    tm.set_start("q0", &format!("a{E_ACUTE}")).unwrap();
+    assert_eq!(tm.run(10).unwrap(), 2);
+    assert_eq!(tm.output(false), "ab");
+}
+
+#[test]
+fn start_line_tape_uses_grapheme_clusters() {
+    let tm = load(&format!("q0 {E_ACUTE}{E_ACUTE}\nqf\n"));
+
+    assert_eq!(tm.get_tape().len(), 2);
+    assert_eq!(tm.get_tape()[0], tm.get_tape()[1]);
+}
+
+#[test]
+fn unary_and_binary_tapes_keep_one_digit_per_cell() {
+    let mut tm = load("q0 1\nqf\n");
+
+    tm.set_unary(3);
+    assert_eq!(tm.get_tape(), &['1', '1', '1']);
+    assert_eq!(tm.read_unary(), Some(3));
+
+    tm.set_binary(6);
+    assert_eq!(tm.get_tape(), &['1', '1', '0']);
+    assert_eq!(tm.read_binary(), Some(6));
+}
+
+#[test]
+fn multi_tape_start_line_splits_like_a_single_tape_one() {
+    let mut tm = MultiTapeMachine::new();
+    let err = tm.load_cfg_str(&format!("tape_count 2\nq0 a{E_ACUTE},b\nqf\n")).unwrap_err();
+    assert!(matches!(err, TmError::Parse { line: 2, .. }), "{err:?}");
+
+    tm.load_cfg_str("tape_count 2\nq0 ab,c\nqf\n").unwrap();
+    assert_eq!(tm.tapes()[0].cells(), &['a', 'b']);
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "logging")]
+
+mod common;
+
+use std::{
+    sync::{Mutex, Once},
+    thread::{self, ThreadId},
+};
+
+use common::load;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Keeps every record with the thread that logged it, so tests running in
+/// parallel only look at their own.
+struct Capture(Mutex<Vec<(ThreadId, Level, String)>>);
+
+impl Log for Capture {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.0.lock().unwrap().push((
+            thread::current().id(),
+            record.level(),
+            record.args().to_string(),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+
+fn records() -> Vec<(Level, String)> {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        log::set_logger(&CAPTURE).unwrap();
+        log::set_max_level(LevelFilter::Trace);
+    });
+
+    let id = thread::current().id();
+    CAPTURE.0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(thread, _, _)| *thread == id)
+        .map(|(_, level, message)| (*level, message.clone()))
+        .collect()
+}
+
+#[test]
+fn transitions_are_traced_and_the_halt_is_logged() {
+    records();
+    let mut tm = load("q0 10\nqf\nq0 1 q0 0 right\nq0 0 qf 1 stay\n");
+    tm.run(10).unwrap();
+
+    assert_eq!(records(), [
+        (Level::Trace, "q0 '1' -> q0 '0' right @cell 0".to_string()),
+        (Level::Trace, "q0 '0' -> qf '1' stay @cell 1".to_string()),
+        (Level::Debug, "halted in qf after 2 steps".to_string()),
+    ]);
+}
+
+#[test]
+fn stuck_machine_is_logged() {
+    records();
+    let mut tm = load("q0 a1\nqf\n");
+    tm.step();
+
+    assert_eq!(records(), [(Level::Debug, "stuck in q0 reading 'a' @cell 0".to_string())]);
+}
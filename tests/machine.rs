@@ -0,0 +1,463 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::{load, FLIP, LOOP};
+use tms::turing_machine::{
+    Direction, RunResult, StepOutcome, TapeMode, TmError, TuringMachine, TuringMachineBuilder,
+};
+
+#[test]
+fn machine_built_in_code_runs_to_halt() {
+    // Flips every bit, then halts on the first blank.
+    let mut tm = TuringMachine::new();
+    tm.set_start("q0", "110").unwrap();
+    tm.set_halt_state("qf");
+    tm.add_instruction("q0", '1', "q0", '0', Direction::Rhs);
+    tm.add_instruction("q0", '0', "q0", '1', Direction::Rhs);
+    tm.add_instruction("q0", '_', "qf", '_', Direction::Stay);
+
+    assert_eq!(tm.run(10).unwrap(), 4);
+    assert_eq!(tm.get_state(), "qf");
+    assert_eq!(tm.trimmed_tape(), &['0', '0', '1']);
+}
+
+#[test]
+fn display_shows_the_loaded_configuration() {
+    let mut tm = TuringMachine::new();
+    tm.load_cfg("examples/odd_or_even.cfg").unwrap();
+
+    assert_eq!(tm.to_string(), "\
+state: q0
+halt states: qHalt
+head: 0
+tape: [|] | | | | | -
+instructions: 4");
+}
+
+#[test]
+fn reset_restores_every_setting_to_its_default() {
+    let duplicates = "q0 1\nqf\nq0 1 qf 1 stay\nq0 1 qf 0 stay\n";
+    let mut tm = TuringMachine::new();
+    tm.set_nondeterministic(true);
+    tm.set_ignore_state_case(true);
+    tm.set_tape_mode(TapeMode::LeftBounded);
+    tm.set_max_tape_size(Some(4));
+    tm.set_history_capacity(8);
+    tm.load_cfg_str(duplicates).unwrap();
+    tm.add_breakpoint("q0");
+    tm.watch_cell(0);
+
+    tm.reset();
+
+    assert_eq!(tm.get_tape_mode(), TapeMode::Infinite);
+    let err = tm.load_cfg_str(duplicates).unwrap_err();
+    assert!(matches!(err, TmError::DuplicateInstruction { .. }), "{err:?}");
+
+    tm.reset();
+    tm.load_cfg_str(LOOP).unwrap();
+    assert_eq!(tm.run_until_breakpoint(10), RunResult::StepLimitExceeded { steps: 10 });
+    tm.step();
+    assert!(!tm.step_back());
+}
+
+#[test]
+#[allow(deprecated)]
+fn machine_may_stop_in_either_of_two_halt_states() {
+    let cfg = "q0 0\nyes\nno\nq0 0 no 0 stay\nq0 1 yes 1 stay\n";
+    let mut tm = TuringMachine::new();
+    tm.load_cfg_str(cfg).unwrap();
+
+    assert_eq!(tm.get_halt_states().collect::<Vec<_>>(), ["no", "yes"]);
+    assert_eq!(tm.get_halt_state(), "no");
+
+    assert_eq!(tm.run(10).unwrap(), 1);
+    assert_eq!(tm.get_state(), "no");
+
+    tm.set_tape("1").unwrap();
+    assert_eq!(tm.run(10).unwrap(), 1);
+    assert_eq!(tm.get_state(), "yes");
+    assert!(tm.is_halt());
+}
+
+#[test]
+fn instructions_give_their_fields_and_symbol_names() {
+    let mut tm = TuringMachine::new();
+    tm.load_cfg_str("start q0\ntape a1\nqf\nq0 a1 q1 b2 right\nq1 _ qf 1 left\n").unwrap();
+
+    let rules: Vec<_> = tm
+        .instructions()
+        .iter()
+        .map(|i| {
+            (
+                i.current_state(),
+                i.current_symbol_name(&tm),
+                i.new_state(),
+                i.new_symbol_name(&tm),
+                i.direction(),
+            )
+        })
+        .collect();
+
+    assert_eq!(rules, [
+        ("q0", "a1".to_string(), "q1", "b2".to_string(), Direction::Rhs),
+        ("q1", "_".to_string(), "qf", "1".to_string(), Direction::Lhs),
+    ]);
+    assert_eq!(tm.instructions()[1].new_symbol(), '1');
+}
+
+#[test]
+fn builder_reports_a_bad_start_tape() {
+    let err = TuringMachineBuilder::new()
+        .start("q0", "1@@0")
+        .halt("qf")
+        .rule("q0", '1', "qf", '1', Direction::Stay)
+        .build()
+        .unwrap_err();
+
+    assert_eq!(err.to_string(), "expected at most one '@' head marker, found 2");
+}
+
+#[test]
+fn clones_run_independently() {
+    let mut tm = TuringMachine::new();
+    tm.load_cfg_str("q0 11\nqf\nq0 1 q0 0 right\nq0 _ qf _ stay\n").unwrap();
+    let mut copy = tm.clone();
+
+    copy.run(10).unwrap();
+    assert_eq!(copy.get_tape(), &['0', '0', '_']);
+    assert_eq!(tm.get_tape(), &['1', '1']);
+    assert_eq!(tm.get_state(), "q0");
+    assert_eq!(tm.get_step_count(), 0);
+
+    tm.add_instruction("q0", '0', "qf", '0', Direction::Stay);
+    assert_eq!(copy.instructions().len(), 2);
+    assert_eq!(tm.instructions().len(), 3);
+}
+
+#[test]
+fn clone_mid_run_is_a_checkpoint() {
+    let mut tm = TuringMachine::new();
+    tm.load_cfg_str("q0 11\nqf\nq0 1 q0 0 right\nq0 _ qf _ stay\n").unwrap();
+    tm.step();
+    let saved = tm.clone();
+
+    tm.run(10).unwrap();
+    assert_eq!(saved.get_state(), "q0");
+    assert_eq!(saved.get_tape(), &['0', '1']);
+    assert_eq!(saved.get_tape_cell(), 1);
+    assert_eq!(saved.get_step_count(), 1);
+
+    tm = saved;
+    assert_eq!(tm.run(10).unwrap(), 2);
+    assert_eq!(tm.get_tape(), &['0', '0', '_']);
+}
+
+#[test]
+fn machines_with_the_same_rules_and_configuration_are_equal() {
+    let mut a = TuringMachine::new();
+    a.load_cfg_str("q0 01\nqf\nq0 0 q0 1 right\nq0 1 qf 1 stay\n").unwrap();
+    let mut b = TuringMachine::new();
+    b.load_cfg_str("q0 01__\nqf\nq0 1 qf 1 stay\nq0 0 q0 1 right\n").unwrap();
+
+    assert_eq!(a, b);
+
+    a.step();
+    assert_ne!(a, b);
+    b.step();
+    assert_eq!(a, b);
+}
+
+#[test]
+fn machines_differing_in_a_symbol_name_are_not_equal() {
+    let load = |symbol: &str| {
+        let mut tm = TuringMachine::new();
+        tm.load_cfg_str(&format!("start q0\ntape {symbol}\nqf\nq0 {symbol} qf {symbol} stay\n"))
+            .unwrap();
+        tm
+    };
+
+    assert_eq!(load("a1"), load("a1"));
+    assert_ne!(load("a1"), load("b1"));
+}
+
+#[test]
+fn machines_interning_symbols_in_another_order_are_equal() {
+    let mut a = TuringMachine::new();
+    a.load_cfg_str("start q0\ntape a1 b1\nqf\nq0 b1 qf a1 stay\n").unwrap();
+    let mut b = TuringMachine::new();
+    b.load_cfg_str("start q0\nqf\nq0 b1 qf a1 stay\ntape a1 b1\n").unwrap();
+    let mut c = TuringMachine::new();
+    c.load_cfg_str("start q0\ntape b1 a1\nqf\nq0 b1 qf a1 stay\n").unwrap();
+
+    assert_ne!(a.symbol("a1"), b.symbol("a1"));
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+}
+
+#[test]
+fn built_machine_runs_to_halt() {
+    let mut tm = TuringMachineBuilder::new()
+        .start("q0", "11")
+        .halt("qf")
+        .rule("q0", '1', "q0", '1', Direction::Rhs)
+        .rule("q0", '_', "qf", '1', Direction::Stay)
+        .build()
+        .unwrap();
+
+    assert_eq!(tm.run(10).unwrap(), 3);
+    assert_eq!(tm.get_tape(), &['1', '1', '1']);
+}
+
+#[test]
+fn builder_blank_pads_the_start_tape_whatever_the_call_order() {
+    let blank_last = TuringMachineBuilder::new()
+        .start("q0", "1@")
+        .halt("qf")
+        .rule("q0", '0', "qf", '1', Direction::Stay)
+        .blank('0')
+        .build()
+        .unwrap();
+    let blank_first = TuringMachineBuilder::new()
+        .blank('0')
+        .start("q0", "1@")
+        .halt("qf")
+        .rule("q0", '0', "qf", '1', Direction::Stay)
+        .build()
+        .unwrap();
+
+    assert_eq!(blank_last.get_tape(), &['1', '0']);
+    assert_eq!(blank_last.head_symbol(), '0');
+    assert_eq!(blank_last, blank_first);
+}
+
+#[test]
+fn builder_numbers_duplicate_rules_from_one() {
+    let err = TuringMachineBuilder::new()
+        .start("q0", "1")
+        .halt("qf")
+        .rule("q0", '1', "qf", '1', Direction::Stay)
+        .rule("q0", '_', "qf", '_', Direction::Stay)
+        .rule("q0", '1', "qf", '0', Direction::Stay)
+        .build()
+        .unwrap_err();
+
+    match err {
+        TmError::DuplicateInstruction { state, symbol, first_line, second_line } => {
+            assert_eq!((state.as_str(), symbol.as_str()), ("q0", "1"));
+            assert_eq!((first_line, second_line), (1, 3));
+        }
+        err => panic!("unexpected error {err:?}"),
+    }
+}
+
+#[test]
+fn from_cfg_loads_a_ready_machine() {
+    let mut tm = TuringMachine::from_cfg("examples/odd_or_even.cfg").unwrap();
+    assert_eq!(tm.run(100).unwrap(), 7);
+    assert_eq!(tm.get_tape().last(), Some(&'E'));
+
+    tm = TuringMachine::from_cfg_str("q0 1\nqf\nq0 1 qf 0 stay\n").unwrap();
+    assert_eq!(tm.run(10).unwrap(), 1);
+    assert_eq!(tm.get_tape(), &['0']);
+}
+
+#[test]
+fn from_cfg_reports_parse_and_validation_errors() {
+    assert!(matches!(TuringMachine::from_cfg("does/not/exist.cfg"), Err(TmError::Io(_))));
+    assert!(matches!(
+        TuringMachine::from_cfg_str("q0 1\nqf\nq0 1 q1 0 stay\n"),
+        Err(TmError::Validation(_)),
+    ));
+}
+
+#[test]
+fn logically_equal_configurations_hash_equally() {
+    let load = |cfg: &str| {
+        let mut tm = TuringMachine::new();
+        tm.load_cfg_str(cfg).unwrap();
+        tm
+    };
+
+    assert_eq!(load("q0 01\nqf\n").config_hash(), load("q0 01___\nqf\n").config_hash());
+
+    // b1 is interned first in one machine and second in the other.
+    let first = load("start q0\ntape b1\nqf\nq0 a1 qf a1 stay\n");
+    let second = load("start q0\nqf\nq0 a1 qf a1 stay\ntape b1\n");
+    assert_ne!(first.get_tape(), second.get_tape());
+    assert_eq!(first.config_hash(), second.config_hash());
+}
+
+#[test]
+fn different_configurations_hash_differently() {
+    let hash = |cfg: &str| {
+        let mut tm = TuringMachine::new();
+        tm.load_cfg_str(cfg).unwrap();
+        tm.config_hash()
+    };
+
+    assert_ne!(hash("q0 01\nqf\n"), hash("q0 10\nqf\n"));
+    assert_ne!(hash("q0 01\nqf\n"), hash("q1 01\nqf\n"));
+    assert_ne!(hash("q0 01\nqf\n"), hash("q0 0@1\nqf\n"));
+    assert_ne!(hash("start q0\ntape a1\nqf\n"), hash("start q0\ntape b1\nqf\n"));
+    assert_ne!(hash("start q0\ntape ab\nqf\n"), hash("q0 ab\nqf\n"));
+}
+
+#[test]
+fn alphabet_and_states_cover_the_rules() {
+    let mut tm = TuringMachine::new();
+    tm.load_cfg_str("start q0\nqf\nqr\nq0 1 q1 x right\nq1 * q0 * left\nq1 _ qf 0 stay\n").unwrap();
+
+    assert_eq!(tm.alphabet().into_iter().collect::<String>(), "01_x");
+    assert_eq!(tm.states().into_iter().collect::<Vec<_>>(), ["q0", "q1", "qf", "qr"]);
+}
+
+#[test]
+fn debug_shows_the_key_fields() {
+    let mut tm = TuringMachine::new();
+    tm.load_cfg_str("q0 1@01\nqf\nq0 0 q0 0 right\nq0 1 qf 1 stay\n").unwrap();
+    let debug = format!("{tm:?}");
+
+    assert!(debug.starts_with("TuringMachine {"), "{debug}");
+    assert!(debug.contains("state: \"q0\""), "{debug}");
+    assert!(debug.contains("halt_states: {\"qf\"}"), "{debug}");
+    assert!(debug.contains("head: 1"), "{debug}");
+    assert!(debug.contains("tape: \"1 [0] 1\""), "{debug}");
+    assert!(debug.contains("instructions: 2"), "{debug}");
+}
+
+#[test]
+fn remapped_symbols_keep_the_behaviour() {
+    let mut tm = load(FLIP);
+    tm.remap_symbols(&HashMap::from([('0', 'a'), ('1', 'b')])).unwrap();
+
+    assert_eq!(tm.get_tape(), &['a', 'b', 'b', 'a']);
+    assert_eq!(tm.run(10).unwrap(), 5);
+    assert_eq!(tm.trimmed_tape(), &['b', 'a', 'a', 'b']);
+    assert!(tm.alphabet().iter().all(|c| !['0', '1'].contains(c)));
+}
+
+#[test]
+fn non_injective_remapping_is_rejected() {
+    let mut tm = load(FLIP);
+    let before = tm.clone();
+
+    let err = tm.remap_symbols(&HashMap::from([('0', '1')])).unwrap_err();
+    assert!(matches!(err, TmError::Validation(_)), "{err:?}");
+    assert!(err.to_string().contains("would both map to"), "{err}");
+    assert_eq!(tm, before);
+}
+
+#[test]
+fn remapping_onto_the_wildcard_or_the_blank_is_rejected() {
+    let mut tm = load(FLIP);
+    let before = tm.clone();
+
+    let err = tm.remap_symbols(&HashMap::from([('0', '*')])).unwrap_err();
+    assert!(matches!(err, TmError::Validation(_)), "{err:?}");
+    assert!(err.to_string().contains("wildcard"), "{err}");
+
+    let err = tm.remap_symbols(&HashMap::from([('0', '_')])).unwrap_err();
+    assert!(matches!(err, TmError::Validation(_)), "{err:?}");
+    assert_eq!(tm, before);
+
+    tm.remap_symbols(&HashMap::from([('0', '_'), ('_', 'b')])).unwrap();
+    assert_eq!(tm.get_blank(), 'b');
+    assert_eq!(tm.get_tape(), &['_', '1', '1', '_']);
+}
+
+#[test]
+fn appended_machine_runs_on_the_tape_left_behind() {
+    // Doubling a binary number appends a 0 and leaves the head on it.
+    let mut double = TuringMachine::new();
+    double
+        .load_cfg_str("q0 101\ndone\nq0 * q0 * right\nq0 _ done 0 stay\n")
+        .unwrap();
+    let mut increment = TuringMachine::new();
+    increment
+        .load_cfg_str("i0 1\nqf\ni0 1 i0 0 left\ni0 0 qf 1 stay\ni0 _ qf 1 stay\n")
+        .unwrap();
+
+    double.append(&increment, "inc_").unwrap();
+
+    assert!(double.states().contains("inc_i0"));
+    assert_eq!(double.run(20).unwrap(), 5);
+    assert_eq!(double.get_state(), "inc_qf");
+    assert_eq!(double.trimmed_tape(), &['1', '0', '1', '1']);
+}
+
+#[test]
+fn get_instruction_for_finds_the_rule_for_a_pair() {
+    let tm = load(FLIP);
+
+    let rule = tm.get_instruction_for("q0", '1').unwrap();
+    assert_eq!(rule.new_state(), "q0");
+    assert_eq!(rule.new_symbol(), '0');
+    assert_eq!(rule.direction(), Direction::Rhs);
+    assert_eq!(tm.get_instruction_for("q0", '_').unwrap().new_state(), "qf");
+}
+
+#[test]
+fn get_instruction_for_an_unknown_pair_is_none() {
+    let tm = load(FLIP);
+
+    assert!(tm.get_instruction_for("q0", 'x').is_none());
+    assert!(tm.get_instruction_for("qf", '0').is_none());
+    assert!(tm.get_instruction_for("q9", '1').is_none());
+}
+
+#[test]
+fn replace_instruction_changes_an_existing_rule() {
+    let mut tm = load(FLIP);
+
+    assert!(tm.replace_instruction("q0", '0', "q0", '0', Direction::Rhs));
+    assert_eq!(tm.instructions().len(), 3);
+    assert_eq!(tm.run(10).unwrap(), 5);
+    assert_eq!(tm.trimmed_tape(), &['0', '0', '0', '0']);
+}
+
+#[test]
+fn replace_instruction_adds_a_missing_rule() {
+    let mut tm = TuringMachine::new();
+    tm.load_cfg_str("q0 1x\nqf\nq0 1 q0 1 right\n").unwrap();
+
+    assert!(!tm.replace_instruction("q0", 'x', "qf", 'y', Direction::Stay));
+    assert_eq!(tm.instructions().len(), 2);
+    assert_eq!(tm.get_instruction_for("q0", 'x').unwrap().new_symbol(), 'y');
+    assert_eq!(tm.run(10).unwrap(), 2);
+    assert_eq!(tm.get_tape(), &['1', 'y']);
+}
+
+#[test]
+fn removed_rule_no_longer_fires() {
+    let mut tm = load(FLIP);
+
+    assert!(tm.remove_instruction("q0", '1'));
+    assert_eq!(tm.instructions().len(), 2);
+    assert!(tm.get_instruction_for("q0", '1').is_none());
+
+    assert_eq!(tm.step(), StepOutcome::Applied);
+    assert_eq!(tm.step(), StepOutcome::NoTransition);
+    assert_eq!(tm.get_tape(), &['1', '1', '1', '0']);
+}
+
+#[test]
+fn removing_an_absent_rule_changes_nothing() {
+    let mut tm = load(FLIP);
+
+    assert!(!tm.remove_instruction("q0", 'x'));
+    assert!(!tm.remove_instruction("qf", '0'));
+    assert_eq!(tm.instructions().len(), 3);
+}
+
+#[test]
+fn cleared_machine_keeps_its_tape_but_has_no_rules() {
+    let mut tm = load(FLIP);
+    tm.clear_instructions();
+
+    assert!(tm.instructions().is_empty());
+    assert_eq!(tm.step(), StepOutcome::NoTransition);
+    assert_eq!(tm.get_tape(), &['0', '1', '1', '0']);
+    assert_eq!(tm.get_state(), "q0");
+}
@@ -0,0 +1,66 @@
+use tms::turing_machine::{MultiTapeMachine, Outcome, TmError};
+
+/// Copies the input of tape 0 onto tape 1.
+const COPY: &str = "\
+tape_count 2
+name copy
+q0 1101,
+accept qf
+q0 1,_ q0 1,1 right,right
+q0 0,_ q0 0,0 right,right
+q0,_,_ -> qf,_,_,stay,stay
+";
+
+fn load(cfg: &str) -> MultiTapeMachine {
+    let mut tm = MultiTapeMachine::new();
+    tm.load_cfg_str(cfg).unwrap();
+    tm
+}
+
+#[test]
+fn two_tape_machine_copies_its_input() {
+    let mut tm = load(COPY);
+
+    assert_eq!(tm.run(100).unwrap(), 5);
+    assert_eq!(tm.tapes()[0].cells(), &['1', '1', '0', '1', '_']);
+    assert_eq!(tm.tapes()[1].cells(), &['1', '1', '0', '1', '_']);
+    assert_eq!(tm.tapes()[1].head_offset(), 4);
+    assert_eq!(tm.name(), Some("copy"));
+    assert_eq!(tm.outcome(), Some(Outcome::Accept));
+}
+
+#[test]
+fn stuck_machine_reports_the_symbol_on_every_tape() {
+    let mut tm = load(&COPY.replace("q0 1101,", "q0 1101,x"));
+
+    match tm.run(100).unwrap_err() {
+        TmError::NoTransition { state, symbol } => {
+            assert_eq!(state, "q0");
+            assert_eq!(symbol, "1,x");
+        }
+        err => panic!("unexpected error {err:?}"),
+    }
+    assert_eq!(tm.outcome(), None);
+}
+
+#[test]
+fn duplicate_rules_name_both_lines() {
+    let mut tm = MultiTapeMachine::new();
+    let err = tm.load_cfg_str(&format!("{COPY}q0 1,_ qf 1,_ stay,stay\n")).unwrap_err();
+
+    match err {
+        TmError::DuplicateInstruction { symbol, first_line, second_line, .. } => {
+            assert_eq!(symbol, "1,_");
+            assert_eq!((first_line, second_line), (5, 8));
+        }
+        err => panic!("unexpected error {err:?}"),
+    }
+}
+
+#[test]
+fn arrow_rule_needs_an_entry_per_tape() {
+    let mut tm = MultiTapeMachine::new();
+    let err = tm.load_cfg_str("tape_count 2\nq0 1,\nqf\nq0,1 -> qf,1,_,R,R\n").unwrap_err();
+
+    assert!(matches!(err, TmError::Parse { line: 4, .. }), "{err:?}");
+}
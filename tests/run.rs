@@ -0,0 +1,928 @@
+mod common;
+
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::{load, FLIP, INCREMENT, LOOP};
+use tms::turing_machine::{
+    Direction, Equivalence, Outcome, RunResult, StepOutcome, TapeMode, TmError, TuringMachine,
+};
+
+#[test]
+fn step_reports_applied_then_halted() {
+    let mut tm = load("q0 1\nqf\nq0 1 qf 0 right\n");
+
+    assert_eq!(tm.step(), StepOutcome::Applied);
+    assert_eq!(tm.get_state(), "qf");
+    assert_eq!(tm.step(), StepOutcome::Halted);
+    assert_eq!(tm.get_tape(), &['0', '_']);
+}
+
+#[test]
+fn step_reports_a_missing_transition() {
+    let mut tm = load("q0 10\nqf\nq0 1 q1 1 right\n");
+
+    assert_eq!(tm.step(), StepOutcome::Applied);
+    assert_eq!(tm.step(), StepOutcome::NoTransition);
+    assert_eq!(tm.get_state(), "q1");
+    assert_eq!(tm.get_tape_cell(), 1);
+    assert_eq!(tm.get_step_count(), 1);
+}
+
+#[test]
+fn run_returns_the_steps_to_halt() {
+    let mut tm = load(INCREMENT);
+
+    assert_eq!(tm.run(100).unwrap(), 3);
+    assert!(tm.is_halt());
+    assert_eq!(tm.get_tape(), &['1', '1', '0', '0']);
+}
+
+#[test]
+fn run_stops_a_looping_machine_at_the_budget() {
+    let mut tm = load(LOOP);
+
+    let err = tm.run(50).unwrap_err();
+    assert!(matches!(err, TmError::StepLimitExceeded(50)), "{err:?}");
+    assert_eq!(tm.get_step_count(), 50);
+}
+
+#[test]
+fn run_reports_a_stuck_machine() {
+    let mut tm = load("q0 10\nqf\nq0 1 q1 1 right\n");
+
+    match tm.run(100).unwrap_err() {
+        TmError::NoTransition { state, .. } => assert_eq!(state, "q1"),
+        err => panic!("unexpected error {err:?}"),
+    }
+}
+
+#[test]
+fn indexed_lookup_matches_a_linear_scan() {
+    // The 4-state busy beaver: 107 steps, leaving 13 ones.
+    let mut tm = load("\
+start A
+blank 0
+H
+A 0 B 1 right
+A 1 B 1 left
+B 0 A 1 left
+B 1 C 0 left
+C 0 H 1 right
+C 1 D 1 left
+D 0 D 1 right
+D 1 A 0 right
+");
+
+    while !tm.is_halt() {
+        let scanned = tm
+            .instructions()
+            .iter()
+            .find(|i| i.current_state() == tm.get_state() && i.current_symbol() == tm.head_symbol());
+
+        assert!(std::ptr::eq(tm.peek_transition().unwrap(), scanned.unwrap()));
+        assert_eq!(tm.step(), StepOutcome::Applied);
+    }
+
+    assert_eq!(tm.get_step_count(), 107);
+    assert_eq!(tm.get_tape().iter().filter(|&&c| c == '1').count(), 13);
+}
+
+#[test]
+fn reset_to_start_reruns_the_same_machine() {
+    let mut tm = load(INCREMENT);
+    tm.run(100).unwrap();
+    let first = tm.get_tape().to_vec();
+
+    tm.reset_to_start();
+    assert_eq!(tm.get_state(), "q0");
+    assert_eq!(tm.get_tape(), &['1', '0', '1', '1']);
+    assert_eq!(tm.get_tape_cell(), 3);
+    assert_eq!(tm.get_step_count(), 0);
+
+    tm.run(100).unwrap();
+    assert_eq!(tm.get_tape(), first.as_slice());
+}
+
+#[test]
+fn step_count_only_counts_applied_transitions() {
+    let mut tm = load("q0 10\nqf\nq0 1 q0 1 right\nq0 0 q1 0 right\n");
+    let mut applied = 0;
+
+    for _ in 0..5 {
+        if tm.step() == StepOutcome::Applied {
+            applied += 1;
+        }
+    }
+
+    assert_eq!(applied, 2);
+    assert_eq!(tm.get_step_count(), 2);
+
+    tm.reset_to_start();
+    assert_eq!(tm.get_step_count(), 0);
+    tm.step();
+    tm.reset();
+    assert_eq!(tm.get_step_count(), 0);
+}
+
+#[test]
+fn peek_transition_shows_the_next_rule_without_stepping() {
+    let tm = load(INCREMENT);
+    let before = tm.snapshot();
+
+    let next = tm.peek_transition().unwrap();
+    assert_eq!(next.current_state(), "q0");
+    assert_eq!(next.current_symbol(), '1');
+    assert_eq!(next.new_symbol(), '0');
+    assert_eq!(next.new_state(), "q0");
+    assert_eq!(next.direction(), Direction::Lhs);
+    assert_eq!(tm.snapshot(), before);
+}
+
+#[test]
+fn peek_transition_is_none_when_stuck() {
+    let tm = load("q0 0\nqf\nq0 1 qf 1 right\n");
+
+    assert!(tm.peek_transition().is_none());
+    assert_eq!(tm.get_step_count(), 0);
+}
+
+#[test]
+fn step_back_restores_each_earlier_configuration() {
+    let mut tm = load("q0 1@1\nqf\nq0 1 q0 0 left\nq0 _ qf 1 stay\n");
+    tm.set_history_capacity(10);
+
+    let mut snapshots = vec![tm.snapshot()];
+    while tm.step() == StepOutcome::Applied {
+        snapshots.push(tm.snapshot());
+    }
+    assert_eq!(tm.get_tape(), &['1', '0', '0']);
+
+    snapshots.pop();
+    while let Some(snapshot) = snapshots.pop() {
+        assert!(tm.step_back());
+        assert_eq!(tm.snapshot(), snapshot);
+        assert_eq!(tm.get_step_count(), snapshots.len());
+    }
+    assert!(!tm.step_back());
+}
+
+#[test]
+fn history_drops_the_oldest_entries_past_its_capacity() {
+    let mut tm = load(LOOP);
+    tm.set_history_capacity(2);
+
+    tm.step();
+    let after_first = tm.snapshot();
+    tm.step();
+    tm.step();
+
+    assert!(tm.step_back());
+    assert!(tm.step_back());
+    assert_eq!(tm.snapshot(), after_first);
+    assert!(!tm.step_back());
+    assert_eq!(tm.get_step_count(), 1);
+}
+
+#[test]
+fn history_is_off_by_default_and_cleared_by_reset() {
+    let mut tm = load(LOOP);
+    tm.step();
+    assert!(!tm.step_back());
+
+    tm.set_history_capacity(4);
+    tm.step();
+    tm.reset();
+    assert!(!tm.step_back());
+}
+
+/// Accepts inputs containing `11` by guessing where the pair starts.
+const HAS_PAIR: &str = "\
+q0 0110
+qf
+q0 0 q0 0 right
+q0 1 q0 1 right
+q0 1 q1 1 right
+q1 1 qf 1 stay
+";
+
+fn nondeterministic(input: &str) -> TuringMachine {
+    let mut tm = TuringMachine::new();
+    tm.set_nondeterministic(true);
+    tm.load_cfg_str(HAS_PAIR).unwrap();
+    tm.set_tape(input).unwrap();
+    tm
+}
+
+#[test]
+fn nondeterministic_run_finds_an_accepting_branch() {
+    let tm = nondeterministic("0110");
+
+    let path = tm.run_nondeterministic(10).unwrap();
+    let states: Vec<&str> = path.iter().map(|c| c.state.as_str()).collect();
+    assert_eq!(states, ["q0", "q0", "q1", "qf"]);
+    assert_eq!(path[0], tm.snapshot());
+    assert_eq!(path[3].tape_cell, 2);
+}
+
+#[test]
+fn nondeterministic_run_rejects_when_no_branch_halts() {
+    assert!(nondeterministic("0101").run_nondeterministic(10).is_none());
+    assert!(nondeterministic("0110").run_nondeterministic(2).is_none());
+}
+
+/// Accepts inputs starting with 1 and rejects those starting with 0.
+const DECIDER: &str = "q0 1\naccept yes\nreject no\nq0 1 yes 1 stay\nq0 0 no 0 stay\n";
+
+#[test]
+fn accept_state_gives_an_accept_outcome() {
+    let mut tm = load(DECIDER);
+    assert_eq!(tm.outcome(), None);
+
+    tm.run(10).unwrap();
+    assert_eq!(tm.outcome(), Some(Outcome::Accept));
+}
+
+#[test]
+fn reject_state_gives_a_reject_outcome() {
+    let mut tm = load(&DECIDER.replacen("q0 1", "q0 0", 1));
+    tm.run(10).unwrap();
+
+    assert_eq!(tm.get_state(), "no");
+    assert_eq!(tm.outcome(), Some(Outcome::Reject));
+}
+
+#[test]
+fn set_halt_state_drops_the_accept_and_reject_states() {
+    let mut tm = load(&DECIDER.replacen("q0 1", "q0 0", 1));
+    tm.set_halt_state("no");
+    tm.run(10).unwrap();
+
+    assert_eq!(tm.get_state(), "no");
+    assert_eq!(tm.outcome(), Some(Outcome::Accept));
+
+    tm.set_halt_state("yes");
+    assert!(!tm.is_halt());
+    assert_eq!(tm.outcome(), None);
+}
+
+#[test]
+fn plain_halt_state_counts_as_accepting() {
+    let mut tm = load(INCREMENT);
+    tm.run(10).unwrap();
+
+    assert_eq!(tm.outcome(), Some(Outcome::Accept));
+}
+
+#[test]
+fn stuck_machine_has_no_outcome() {
+    let mut tm = load("q0 10\nqf\nq0 1 q1 1 right\n");
+    tm.run(10).unwrap_err();
+
+    assert_eq!(tm.outcome(), None);
+}
+
+#[test]
+fn exit_code_follows_the_outcome() {
+    let mut accept = load(DECIDER);
+    assert_eq!(accept.exit_code(), 2);
+    accept.run(10).unwrap();
+    assert_eq!(accept.exit_code(), 0);
+
+    let mut reject = load(&DECIDER.replacen("q0 1", "q0 0", 1));
+    reject.run(10).unwrap();
+    assert_eq!(reject.exit_code(), 1);
+
+    let mut halt = load(INCREMENT);
+    halt.run(10).unwrap();
+    assert_eq!(halt.exit_code(), 0);
+}
+
+#[test]
+fn exit_code_of_a_stuck_or_running_machine_is_two() {
+    let mut stuck = load("q0 10\nqf\nq0 1 q1 1 right\n");
+    stuck.run(10).unwrap_err();
+    assert_eq!(stuck.exit_code(), 2);
+
+    let mut running = load(LOOP);
+    running.run(10).unwrap_err();
+    assert_eq!(running.exit_code(), 2);
+}
+
+#[test]
+fn nondeterministic_search_skips_a_shallower_reject_branch() {
+    let mut tm = TuringMachine::new();
+    tm.set_nondeterministic(true);
+    tm.load_cfg_str("\
+q0 1
+accept yes
+reject no
+q0 1 no 1 stay
+q0 1 q1 1 right
+q1 _ yes _ stay
+").unwrap();
+
+    let path = tm.run_nondeterministic(10).unwrap();
+    assert_eq!(path.len(), 3);
+    assert_eq!(path.last().unwrap().state, "yes");
+
+    tm.remove_instruction("q1", '_');
+    assert!(tm.run_nondeterministic(10).is_none());
+}
+
+#[test]
+fn exact_rule_takes_precedence_over_a_wildcard() {
+    // The wildcard rule comes first but only covers symbols other than 1.
+    let mut tm = load("q0 1a0\nqf\nq0 * q0 x right\nq0 1 q0 y right\nq0 _ qf _ stay\n");
+    tm.run(10).unwrap();
+
+    assert_eq!(tm.trimmed_tape(), &['y', 'x', 'x']);
+}
+
+#[test]
+fn wildcard_write_leaves_the_cell_unchanged() {
+    let mut tm = load("q0 ab1\nqf\nq0 * q0 * right\nq0 1 q0 0 right\nq0 _ qf _ stay\n");
+    tm.run(10).unwrap();
+
+    assert_eq!(tm.trimmed_tape(), &['a', 'b', '0']);
+    assert_eq!(tm.get_state(), "qf");
+}
+
+#[test]
+fn multi_character_symbols_run_and_report_their_names() {
+    let mut tm = load("start q0\ntape a1 b2 c3\nqf\nq0 a1 q0 b2 right\nq0 b2 q0 a1 right\n");
+    let err = tm.run(10).unwrap_err();
+
+    let names: Vec<String> = tm.get_tape().iter().map(|&c| tm.symbol_name(c)).collect();
+    assert_eq!(names, ["b2", "a1", "c3"]);
+    match err {
+        TmError::NoTransition { state, symbol } => {
+            assert_eq!(state, "q0");
+            assert_eq!(symbol, "c3");
+        }
+        err => panic!("unexpected error {err:?}"),
+    }
+    assert_eq!(tm.get_state(), "q0");
+}
+
+#[test]
+fn configurations_match_manual_stepping() {
+    let mut manual = load(INCREMENT);
+    let mut expected = vec![manual.snapshot()];
+    while manual.step() == StepOutcome::Applied {
+        expected.push(manual.snapshot());
+    }
+
+    let mut tm = load(INCREMENT);
+    let configurations: Vec<_> = tm.configurations().collect();
+
+    assert_eq!(configurations, expected);
+    assert_eq!(configurations.last().unwrap().state, "qf");
+    assert_eq!(tm, manual);
+}
+
+#[test]
+fn bounded_configurations_stop_at_the_limit() {
+    let mut tm = load(LOOP);
+    let configurations: Vec<_> = tm.configurations_bounded(3).collect();
+
+    assert_eq!(configurations.len(), 4);
+    assert_eq!(configurations[3].tape_cell, 3);
+    assert_eq!(tm.get_step_count(), 3);
+}
+
+#[test]
+fn oscillating_between_two_cells_is_a_detected_loop() {
+    let mut tm = load("q0 00\nqf\nq0 0 q1 0 right\nq1 0 q0 0 left\n");
+    let err = tm.run_detect_loop(1000, 100).unwrap_err();
+
+    assert!(matches!(err, TmError::LoopDetected(2)), "{err:?}");
+    assert_eq!(tm.get_step_count(), 2);
+}
+
+#[test]
+fn loop_detection_lets_halting_machines_finish() {
+    let mut tm = load(INCREMENT);
+
+    assert_eq!(tm.run_detect_loop(1000, 100).unwrap(), load(INCREMENT).run(1000).unwrap());
+}
+
+#[test]
+fn one_machine_runs_on_several_inputs() {
+    let mut tm = load(INCREMENT);
+
+    for (input, output) in [("101@1", "1100"), ("11@1", "1000"), ("10@0", "101")] {
+        tm.set_tape(input).unwrap();
+        tm.run(100).unwrap();
+        assert_eq!(tm.trimmed_tape().iter().collect::<String>(), output, "{input}");
+    }
+}
+
+#[test]
+fn set_tape_reads_words_as_multi_character_symbols() {
+    let mut tm = load("start q0\ntape a1\nqf\nq0 a1 q0 b2 right\nq0 _ qf _ stay\n");
+    tm.set_tape("a1 a1 a1").unwrap();
+    tm.run(10).unwrap();
+
+    let names: Vec<String> = tm.trimmed_tape().iter().map(|&c| tm.symbol_name(c)).collect();
+    assert_eq!(names, ["b2", "b2", "b2"]);
+
+    assert!(matches!(tm.set_tape("1@0@1"), Err(TmError::Validation(_))));
+}
+
+#[test]
+fn stats_count_visits_and_the_head_range() {
+    let mut tm = load("q0 1@1\nqf\nq0 1 q0 0 left\nq0 _ qf 1 stay\n");
+    tm.set_history_capacity(10);
+    tm.run(10).unwrap();
+
+    let stats = tm.stats();
+    assert_eq!(stats.steps, 3);
+    assert_eq!(stats.distinct_states, 2);
+    assert_eq!(stats.visits.get("q0"), Some(&3));
+    assert_eq!(stats.visits.get("qf"), Some(&1));
+    assert_eq!((stats.leftmost, stats.rightmost), (-1, 1));
+    assert_eq!(stats.tape_len, 3);
+
+    tm.step_back();
+    tm.step_back();
+    let stats = tm.stats();
+    assert_eq!(stats.steps, 1);
+    assert_eq!(stats.visits.get("q0"), Some(&2));
+    assert_eq!(stats.visits.get("qf"), None);
+    assert_eq!((stats.leftmost, stats.rightmost), (0, 1));
+    assert_eq!(stats.tape_len, 2);
+
+    tm.step_back();
+    assert!(tm.stats().to_string().contains("head range: 1..=1"));
+}
+
+#[test]
+fn run_steps_stops_early_when_the_machine_halts() {
+    let mut tm = load(INCREMENT);
+
+    assert_eq!(tm.run_steps(10), (3, StepOutcome::Halted));
+    assert_eq!(tm.get_step_count(), 3);
+
+    let mut tm = load(INCREMENT);
+    assert_eq!(tm.run_steps(3), (3, StepOutcome::Halted));
+}
+
+#[test]
+fn run_steps_applies_all_steps_of_a_running_machine() {
+    let mut tm = load(LOOP);
+
+    assert_eq!(tm.run_steps(5), (5, StepOutcome::Applied));
+    assert_eq!(tm.run_steps(0), (0, StepOutcome::Applied));
+    assert_eq!(tm.get_tape_cell(), 5);
+}
+
+#[test]
+fn observer_sees_every_applied_transition() {
+    let mut tm = load(INCREMENT);
+    let mut events = Vec::new();
+
+    let steps = tm
+        .run_with_observer(100, |event| {
+            events.push((
+                event.instruction.current_symbol(),
+                event.instruction.new_symbol(),
+                event.head_before,
+                event.state.to_string(),
+            ));
+        })
+        .unwrap();
+
+    assert_eq!(events.len(), steps);
+    assert_eq!(events, [
+        ('1', '0', 3, "q0".to_string()),
+        ('1', '0', 2, "q0".to_string()),
+        ('0', '1', 1, "qf".to_string()),
+    ]);
+}
+
+#[test]
+fn step_observed_skips_the_observer_when_nothing_fires() {
+    let mut tm = load("q0 0\nqf\nq0 1 qf 1 right\n");
+    let mut calls = 0;
+
+    assert_eq!(tm.step_observed(|_| calls += 1), StepOutcome::NoTransition);
+    assert_eq!(calls, 0);
+}
+
+#[test]
+fn restore_puts_back_a_snapshot_taken_mid_run() {
+    let mut tm = load(INCREMENT);
+    tm.step();
+    let snapshot = tm.snapshot();
+
+    tm.run(10).unwrap();
+    assert_ne!(tm.snapshot(), snapshot);
+
+    tm.restore(&snapshot);
+    assert_eq!(tm.snapshot(), snapshot);
+    assert_eq!(tm.get_state(), "q0");
+    assert_eq!(tm.get_tape_cell(), 2);
+    assert_eq!(tm.get_tape(), &['1', '0', '1', '0']);
+
+    tm.run(10).unwrap();
+    assert_eq!(tm.get_tape(), &['1', '1', '0', '0']);
+}
+
+#[test]
+fn step_detailed_returns_the_fired_instruction_indices() {
+    let mut tm = load(INCREMENT);
+    let mut fired = Vec::new();
+    while let Some(i) = tm.step_detailed() {
+        fired.push(i);
+    }
+
+    assert_eq!(fired, [0, 0, 1]);
+    assert_eq!(tm.step_detailed(), None);
+    assert!(tm.is_halt());
+}
+
+#[test]
+fn describe_next_explains_the_pending_transition() {
+    assert_eq!(
+        load(INCREMENT).describe_next().unwrap(),
+        "In state q0 reading 1: write 0, move left, enter q0",
+    );
+    assert_eq!(
+        load("q0 1\nqf\nq0 1 qf * stay\n").describe_next().unwrap(),
+        "In state q0 reading 1: write 1, stay, enter qf",
+    );
+}
+
+#[test]
+fn describe_next_is_none_when_stuck() {
+    assert_eq!(load("q0 0\nqf\nq0 1 qf 1 right\n").describe_next(), None);
+}
+
+#[test]
+fn describe_next_reports_a_halted_machine() {
+    let mut tm = load(INCREMENT);
+    tm.run(10).unwrap();
+
+    assert_eq!(tm.describe_next().unwrap(), "Halted in state qf");
+}
+
+#[test]
+fn run_for_times_out_a_looping_machine() {
+    let mut tm = load("q0 00\nqf\nq0 0 q1 0 right\nq1 0 q0 0 left\n");
+    let start = Instant::now();
+
+    match tm.run_for(Duration::from_millis(20)) {
+        Err(TmError::TimedOut(steps)) => assert_eq!(steps, tm.get_step_count()),
+        result => panic!("unexpected result {result:?}"),
+    }
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn run_for_lets_a_halting_machine_finish() {
+    let mut tm = load(INCREMENT);
+
+    assert_eq!(tm.run_for(Duration::from_secs(5)).unwrap(), 3);
+}
+
+#[test]
+fn halted_machine_is_not_stuck() {
+    let mut tm = load(INCREMENT);
+    assert!(!tm.is_stuck());
+
+    tm.run(10).unwrap();
+    assert!(tm.is_halt());
+    assert!(!tm.is_stuck());
+}
+
+#[test]
+fn machine_without_a_rule_to_apply_is_stuck() {
+    let mut tm = load("q0 10\nqf\nq0 1 q1 1 right\n");
+    tm.step();
+
+    assert!(tm.is_stuck());
+    assert!(!tm.is_halt());
+}
+
+#[test]
+fn step_while_stops_when_the_head_reaches_a_cell() {
+    let mut tm = load(LOOP);
+
+    assert_eq!(tm.step_while(100, |tm| tm.get_tape_cell() < 4).unwrap(), 4);
+    assert_eq!(tm.get_tape_cell(), 4);
+    assert!(matches!(
+        tm.step_while(10, |_| true),
+        Err(TmError::StepLimitExceeded(10)),
+    ));
+}
+
+#[test]
+fn step_while_stops_without_error_at_a_halt() {
+    let mut tm = load(INCREMENT);
+
+    assert_eq!(tm.step_while(100, |_| true).unwrap(), 3);
+    assert!(tm.is_halt());
+}
+
+const WALK: &str = "q0 10\nqf\nq0 1 q1 1 right\nq1 0 q2 0 right\nq2 _ qf _ stay\n";
+
+#[test]
+fn run_pauses_on_entering_a_breakpoint_state() {
+    let mut tm = load(WALK);
+    tm.add_breakpoint("q2");
+
+    assert_eq!(
+        tm.run_until_breakpoint(100),
+        RunResult::Breakpoint { state: "q2".to_string(), symbol: None, steps: 2 },
+    );
+    assert_eq!(tm.run_until_breakpoint(100), RunResult::Halted { steps: 1 });
+}
+
+#[test]
+fn run_ignores_breakpoints_it_never_reaches() {
+    let mut tm = load(WALK);
+    tm.add_breakpoint("q9");
+    assert_eq!(tm.run_until_breakpoint(100), RunResult::Halted { steps: 3 });
+
+    let mut tm = load(WALK);
+    tm.add_breakpoint("q2");
+    assert_eq!(tm.run_until_breakpoint(1), RunResult::StepLimitExceeded { steps: 1 });
+    assert!(tm.remove_breakpoint("q2"));
+    assert_eq!(tm.run_until_breakpoint(100), RunResult::Halted { steps: 2 });
+}
+
+#[test]
+fn symbol_breakpoint_pauses_before_its_rule_fires() {
+    let mut tm = load("q0 110\nqf\nq0 1 q0 1 right\nq0 0 q0 0 right\nq0 _ qf _ stay\n");
+    tm.add_breakpoint_sym("q0", '0');
+
+    assert_eq!(
+        tm.run_until_breakpoint(100),
+        RunResult::Breakpoint { state: "q0".to_string(), symbol: Some('0'), steps: 2 },
+    );
+    assert_eq!(tm.get_tape_cell(), 2);
+    assert_eq!(tm.head_symbol(), '0');
+}
+
+#[test]
+fn symbol_breakpoint_is_skipped_for_other_symbols() {
+    let mut tm = load("q0 111\nqf\nq0 1 q0 1 right\nq0 0 q0 0 right\nq0 _ qf _ stay\n");
+    tm.add_breakpoint_sym("q0", '0');
+
+    assert_eq!(tm.run_until_breakpoint(100), RunResult::Halted { steps: 4 });
+}
+
+#[test]
+fn watchpoint_pauses_on_the_step_that_overwrites_the_cell() {
+    // Walks left off the input and back before overwriting cell 2.
+    let mut tm = load("\
+q0 1@12
+qf
+q0 1 q0 1 left
+q0 _ q1 _ right
+q1 1 q1 1 right
+q1 2 qf 0 stay
+");
+    tm.watch_cell(2);
+    tm.watch_cell(0);
+
+    assert_eq!(
+        tm.run_until_breakpoint(100),
+        RunResult::Watchpoint { cell: 2, old: '2', new: '0', steps: 6 },
+    );
+    assert_eq!(tm.get_tape(), &['_', '1', '1', '0']);
+    assert!(tm.unwatch_cell(2));
+}
+
+#[test]
+fn watchpoint_left_of_the_input_fires() {
+    let mut tm = load("q0 1\nqf\nq0 1 q0 1 left\nq0 _ q1 x right\nq1 1 qf 1 stay\n");
+    tm.watch_cell(-1);
+
+    assert_eq!(
+        tm.run_until_breakpoint(100),
+        RunResult::Watchpoint { cell: -1, old: '_', new: 'x', steps: 2 },
+    );
+    assert_eq!(tm.get_tape(), &['x', '1']);
+    assert!(tm.unwatch_cell(-1));
+    assert!(!tm.unwatch_cell(-1));
+}
+
+#[test]
+fn unchanged_write_behaves_like_writing_the_same_symbol() {
+    let mut wildcard = load("q0 0110\nqf\nq0 0 q0 * right\nq0 1 q0 * right\nq0 _ qf * stay\n");
+    let mut explicit = load("q0 0110\nqf\nq0 0 q0 0 right\nq0 1 q0 1 right\nq0 _ qf _ stay\n");
+
+    let wildcard: Vec<_> = wildcard.configurations().collect();
+    let explicit: Vec<_> = explicit.configurations().collect();
+    assert_eq!(wildcard, explicit);
+    assert_eq!(wildcard.last().unwrap().tape, ['0', '1', '1', '0', '_']);
+}
+
+#[test]
+fn machines_computing_the_same_tape_are_equivalent() {
+    // Both flip every bit, one of them by way of an extra state.
+    let mut a = load(FLIP);
+    let mut b = load("\
+q0 0
+qf
+q0 0 q1 1 right
+q0 1 q1 0 right
+q1 0 q0 1 right
+q1 1 q0 0 right
+q0 _ qf _ stay
+q1 _ qf _ stay
+");
+
+    assert_eq!(a.run_equivalent(&mut b, "0110", 100).unwrap(), Equivalence::Equivalent);
+    assert_eq!(a.trimmed_tape(), &['1', '0', '0', '1']);
+}
+
+#[test]
+fn run_equivalent_tells_how_machines_differ() {
+    let flip = || load(FLIP);
+    let keep = || load("q0 0\nqf\nq0 * q0 * right\nq0 _ qf _ stay\n");
+    let reject = || load("q0 0\nreject qr\nq0 * q0 * right\nq0 _ qr _ stay\n");
+
+    let compare = |mut a: TuringMachine, mut b: TuringMachine| {
+        a.run_equivalent(&mut b, "11", 100).unwrap()
+    };
+    assert_eq!(compare(flip(), keep()), Equivalence::DifferentTape);
+    assert_eq!(compare(keep(), reject()), Equivalence::DifferentOutcome);
+    assert_eq!(compare(flip(), load(LOOP)), Equivalence::OnlyFirstHalts);
+    assert_eq!(compare(load(LOOP), flip()), Equivalence::OnlySecondHalts);
+    assert_eq!(compare(load(LOOP), load(LOOP)), Equivalence::NeitherHalts);
+}
+
+#[test]
+fn streamed_snapshots_match_a_synchronous_run() {
+    let mut expected = Vec::new();
+    let mut tm = load(INCREMENT);
+    while tm.step() == StepOutcome::Applied {
+        expected.push(tm.snapshot());
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let runner = thread::spawn(move || load(INCREMENT).run_streaming(10, tx));
+    let streamed: Vec<_> = rx.iter().collect();
+
+    assert_eq!(runner.join().unwrap().unwrap(), 3);
+    assert_eq!(streamed, expected);
+}
+
+#[test]
+fn streaming_stops_without_error_once_the_receiver_is_gone() {
+    let (tx, rx) = mpsc::channel();
+    drop(rx);
+    assert_eq!(load(LOOP).run_streaming(100, tx).unwrap(), 1);
+
+    let (tx, _rx) = mpsc::channel();
+    let err = load(LOOP).run_streaming(5, tx).unwrap_err();
+    assert!(matches!(err, TmError::StepLimitExceeded(5)), "{err:?}");
+}
+
+#[test]
+fn run_until_stops_when_the_predicate_holds() {
+    let mut tm = load(LOOP);
+
+    assert_eq!(tm.run_until(100, |tm| tm.get_tape_cell() == 4), RunResult::Stopped { steps: 4 });
+    assert_eq!(tm.run_until(100, |_| true), RunResult::Stopped { steps: 0 });
+}
+
+#[test]
+fn run_until_reports_a_halt() {
+    let mut calls = 0;
+    let result = load(INCREMENT).run_until(100, |_| {
+        calls += 1;
+        false
+    });
+
+    assert_eq!(result, RunResult::Halted { steps: 3 });
+    assert_eq!(calls, 4);
+}
+
+#[test]
+fn run_until_checks_the_predicate_after_the_last_step() {
+    let result = load(INCREMENT).run_until(100, |tm| tm.get_state() == "qf");
+    assert_eq!(result, RunResult::Stopped { steps: 3 });
+
+    let result = load(LOOP).run_until(5, |tm| tm.get_tape_cell() == 5);
+    assert_eq!(result, RunResult::Stopped { steps: 5 });
+}
+
+#[test]
+fn run_until_reports_the_step_limit() {
+    assert_eq!(load(LOOP).run_until(5, |_| false), RunResult::StepLimitExceeded { steps: 5 });
+}
+
+#[test]
+fn run_until_reports_a_missing_transition() {
+    let mut tm = load("q0 10\nqf\nq0 1 q0 1 right\n");
+    assert_eq!(tm.run_until(100, |_| false), RunResult::NoTransition { steps: 1 });
+}
+
+#[test]
+fn run_until_reports_a_left_bound_violation() {
+    let mut tm = load("q0 1\nqf\nq0 1 q0 0 left\n");
+    tm.set_tape_mode(TapeMode::LeftBounded);
+
+    assert_eq!(tm.run_until(100, |_| false), RunResult::LeftBoundViolation { steps: 0 });
+}
+
+#[test]
+fn run_until_reports_the_tape_limit() {
+    let mut tm = load(LOOP);
+    tm.set_max_tape_size(Some(3));
+
+    assert_eq!(tm.run_until(100, |_| false), RunResult::TapeLimitExceeded { steps: 2 });
+}
+
+#[test]
+fn tape_history_matches_manual_stepping() {
+    let mut expected = Vec::new();
+    let mut tm = load(INCREMENT);
+    expected.push(tm.get_tape().to_vec());
+    while tm.step() == StepOutcome::Applied {
+        expected.push(tm.get_tape().to_vec());
+    }
+
+    let history = load(INCREMENT).run_tape_history(10).unwrap();
+    assert_eq!(history, expected);
+    assert_eq!(history.len(), 4);
+    assert_eq!(history.first().unwrap(), &['1', '0', '1', '1']);
+    assert_eq!(history.last().unwrap(), &['1', '1', '0', '0']);
+}
+
+#[test]
+fn tape_history_fails_like_run() {
+    let err = load(LOOP).run_tape_history(5).unwrap_err();
+    assert!(matches!(err, TmError::StepLimitExceeded(5)), "{err:?}");
+}
+
+#[test]
+fn ignoring_state_case_applies_to_halt_states() {
+    let cfg = "q0 1\nreject QF\nQ0 1 q0 0 right\nq0 _ qf _ stay\n";
+
+    let mut tm = load(cfg);
+    let err = tm.run(10).unwrap_err();
+    assert!(matches!(err, TmError::NoTransition { .. }), "{err:?}");
+
+    let mut tm = load(cfg);
+    tm.set_ignore_state_case(true);
+    assert_eq!(tm.run(10).unwrap(), 2);
+    assert!(tm.is_halt());
+    assert_eq!(tm.get_state(), "qf");
+    assert_eq!(tm.outcome(), Some(Outcome::Reject));
+}
+
+#[test]
+fn nondeterministic_search_ignores_halt_state_case() {
+    let mut tm = TuringMachine::new();
+    tm.set_nondeterministic(true);
+    tm.load_cfg_str(&HAS_PAIR.replace("qf\n", "QF\n")).unwrap();
+    assert!(tm.run_nondeterministic(20).is_none());
+
+    tm.set_ignore_state_case(true);
+    let path = tm.run_nondeterministic(20).unwrap();
+    assert_eq!(path.last().unwrap().state, "qf");
+}
+
+#[test]
+fn initial_configuration_survives_a_run() {
+    let mut tm = load(INCREMENT);
+    let before = tm.initial_configuration();
+
+    tm.run(10).unwrap();
+    assert_eq!(tm.initial_configuration(), before);
+    assert_eq!(before.state, "q0");
+    assert_eq!(before.tape, ['1', '0', '1', '1']);
+    assert_eq!(before.tape_cell, 3);
+
+    tm.reset_to_start();
+    assert_eq!(tm.snapshot(), before);
+}
+
+#[test]
+fn initial_configuration_follows_set_tape_and_set_head() {
+    let mut tm = load(INCREMENT);
+    tm.run(10).unwrap();
+
+    tm.set_tape("10@1").unwrap();
+    let initial = tm.initial_configuration();
+    assert_eq!(initial.tape, ['1', '0', '1']);
+    assert_eq!(initial.tape_cell, 2);
+
+    tm.run(10).unwrap();
+    tm.set_head(0).unwrap();
+    let initial = tm.initial_configuration();
+    assert_eq!(initial.tape, ['1', '0', '1']);
+    assert_eq!(initial.tape_cell, 0);
+    assert_eq!(initial.state, "q0");
+
+    tm.reset_to_start();
+    assert_eq!(tm.snapshot(), initial);
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::load;
+use tms::turing_machine::{Direction, TraceEntry, TuringMachine};
+
+#[test]
+fn json_round_trip_behaves_identically() {
+    let mut tm = load("name flip\nstart q0\ntape a1 0 1\naccept qf\nq0 a1 q0 b1 right\nq0 0 q0 1 right\nq0 1 q0 0 right\nq0 _ qf _ left\n");
+    tm.step();
+
+    let json = serde_json::to_string(&tm).unwrap();
+    let mut copy: TuringMachine = serde_json::from_str(&json).unwrap();
+    assert_eq!(copy, tm);
+    assert_eq!(copy.name(), Some("flip"));
+
+    tm.run(10).unwrap();
+    copy.run(10).unwrap();
+    assert_eq!(copy, tm);
+    assert_eq!(copy.output(true), "b110");
+}
+
+#[test]
+fn json_names_symbols_and_directions() {
+    let tm = load("start q0\ntape a1\nqf\nq0 a1 qf b1 right\n");
+    let json: serde_json::Value = serde_json::to_value(&tm).unwrap();
+
+    assert_eq!(json["tape"], serde_json::json!(["a1"]));
+    assert_eq!(json["instructions"][0]["current_symbol"], "a1");
+    assert_eq!(json["instructions"][0]["new_symbol"], "b1");
+    assert_eq!(json["instructions"][0]["direction"], "right");
+    assert!(json.get("visits").is_none());
+}
+
+#[test]
+fn hand_written_json_loads_a_runnable_machine() {
+    let mut tm: TuringMachine = serde_json::from_str(r#"{
+        "state": "q0",
+        "halt_states": ["qf"],
+        "tape": ["1", "1"],
+        "instructions": [
+            {"current_state": "q0", "current_symbol": "1", "new_state": "q0", "new_symbol": "0", "direction": "right"},
+            {"current_state": "q0", "current_symbol": "_", "new_state": "qf", "new_symbol": "_", "direction": "stay"}
+        ]
+    }"#).unwrap();
+
+    assert_eq!(tm.get_blank(), '_');
+    assert_eq!(tm.instructions()[0].direction(), Direction::Rhs);
+    assert_eq!(tm.run(10).unwrap(), 3);
+    assert_eq!(tm.trimmed_tape(), &['0', '0']);
+
+    tm.reset_to_start();
+    assert_eq!(tm.get_state(), "q0");
+    assert_eq!(tm.get_tape(), &['1', '1']);
+    assert_eq!(tm.run(10).unwrap(), 3);
+}
+
+#[test]
+fn json_with_the_head_off_the_tape_is_rejected() {
+    let err = serde_json::from_str::<TuringMachine>(
+        r#"{"state": "q0", "halt_states": [], "tape": ["1"], "head": 3, "instructions": []}"#,
+    ).unwrap_err();
+
+    assert!(err.to_string().contains("cell 3 is outside the tape of length 1"), "{err}");
+}
+
+#[test]
+fn trace_records_every_transition() {
+    let mut tm = load("q0 1@1\nqf\nq0 1 q0 0 left\nq0 0 qf 1 stay\nq0 _ qf 1 stay\n");
+    let trace = tm.run_trace(10).unwrap();
+
+    let entry = |step, head, read: &str, written: &str, direction, new_state: &str| TraceEntry {
+        step,
+        state: "q0".to_string(),
+        head,
+        read: read.to_string(),
+        written: written.to_string(),
+        direction,
+        new_state: new_state.to_string(),
+    };
+    assert_eq!(trace, [
+        entry(1, 1, "1", "0", Direction::Lhs, "q0"),
+        entry(2, 0, "1", "0", Direction::Lhs, "q0"),
+        entry(3, -1, "_", "1", Direction::Stay, "qf"),
+    ]);
+
+    let json = serde_json::to_value(&trace[2]).unwrap();
+    assert_eq!(json, serde_json::json!({
+        "step": 3,
+        "state": "q0",
+        "head": -1,
+        "read": "_",
+        "written": "1",
+        "direction": "stay",
+        "new_state": "qf",
+    }));
+}
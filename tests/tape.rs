@@ -0,0 +1,298 @@
+mod common;
+
+use common::{load, LOOP};
+use tms::turing_machine::{
+    Configuration, Direction, StepOutcome, TapeMode, TmError, TuringMachine,
+};
+
+#[test]
+fn walking_past_the_right_edge_fills_blanks() {
+    let mut tm = load("q0 01\nqf\nq0 0 q0 0 right\nq0 1 q0 1 right\nq0 _ q0 _ right\n");
+
+    for _ in 0..22 {
+        assert_eq!(tm.step(), StepOutcome::Applied);
+    }
+
+    assert_eq!(tm.get_tape_cell(), 22);
+    assert_eq!(tm.get_tape().len(), 23);
+    assert_eq!(&tm.get_tape()[..2], &['0', '1']);
+    assert!(tm.get_tape()[2..].iter().all(|&c| c == '_'));
+}
+
+#[test]
+fn moving_left_off_the_start_prepends_blanks() {
+    let mut tm = load("q0 ab\nqf\nq0 a q0 a left\nq0 _ q0 _ left\n");
+
+    for i in 1..=3 {
+        assert_eq!(tm.step(), StepOutcome::Applied);
+        assert_eq!(tm.get_tape_cell(), 0);
+        assert_eq!(tm.get_head_offset(), -i);
+    }
+
+    assert_eq!(tm.get_tape(), &['_', '_', '_', 'a', 'b']);
+    assert_eq!(tm.head_symbol(), '_');
+}
+
+#[test]
+fn long_walks_left_keep_the_input_in_place() {
+    let mut tm = load("q0 1\nqf\nq0 1 q0 1 left\nq0 _ q0 _ left\n");
+
+    assert_eq!(tm.run_steps(10_000).0, 10_000);
+
+    let tape = tm.get_tape();
+    assert_eq!(tape.len(), 10_001);
+    assert_eq!(tape[10_000], '1');
+    assert!(tape[..10_000].iter().all(|&c| c == '_'));
+    assert_eq!(tm.get_head_offset(), -10_000);
+    assert_eq!(tm.tape_slice(-1, 3), vec!['_', '1', '_']);
+}
+
+#[test]
+fn render_tape_marks_the_head() {
+    assert_eq!(load("q0 0110\nqf\n").render_tape(32), "[0] 1 1 0");
+    assert_eq!(load("q0 01@10\nqf\n").render_tape(32), "0 1 [1] 0");
+    assert_eq!(load("q0 011@0\nqf\n").render_tape(32), "0 1 1 [0]");
+    assert_eq!(load("q0 0110@\nqf\n").render_tape(32), "0 1 1 0 [_]");
+}
+
+#[test]
+fn render_tape_shows_a_window_around_the_head() {
+    assert_eq!(load("q0 01234@56789\nqf\n").render_tape(3), "...4 [5] 6...");
+    assert_eq!(load("q0 0123456789\nqf\n").render_tape(3), "[0] 1 2...");
+    assert_eq!(load("q0 012345678@9\nqf\n").render_tape(3), "...7 8 [9]");
+}
+
+#[test]
+fn new_cells_use_the_configured_blank() {
+    let mut tm = load("blank 0\nq0 1\nqf\nq0 1 q1 1 right\nq1 0 q2 0 left\nq2 1 q2 1 left\n");
+    tm.step();
+    assert_eq!(tm.get_tape(), &['1', '0']);
+
+    tm.step();
+    tm.step();
+    assert_eq!(tm.get_tape(), &['0', '1', '0']);
+    assert_eq!(tm.get_blank(), '0');
+}
+
+#[test]
+fn blank_defaults_to_underscore() {
+    let mut tm = load("q0 1\nqf\nq0 1 q0 1 right\n");
+    tm.step();
+
+    assert_eq!(tm.get_blank(), '_');
+    assert_eq!(tm.get_tape(), &['1', '_']);
+}
+
+#[test]
+fn manual_edits_grow_the_tape_at_both_edges() {
+    let mut tm = load("q0 1\nqf\nq1 y qf z stay\n");
+
+    tm.move_head(Direction::Rhs);
+    assert_eq!(tm.get_tape(), &['1', '_']);
+    tm.write('x');
+    assert_eq!(tm.get_tape(), &['1', 'x']);
+
+    tm.move_head(Direction::Lhs);
+    tm.move_head(Direction::Lhs);
+    assert_eq!(tm.get_tape(), &['_', '1', 'x']);
+    assert_eq!(tm.get_head_offset(), -1);
+    tm.write('y');
+    tm.move_head(Direction::Stay);
+    assert_eq!(tm.head_symbol(), 'y');
+
+    tm.set_state("q1");
+    assert_eq!(tm.step(), StepOutcome::Applied);
+    assert_eq!(tm.get_tape(), &['z', '1', 'x']);
+    assert!(tm.is_halt());
+}
+
+#[test]
+fn set_head_after_a_leftward_run_indexes_the_start_tape() {
+    let mut tm = load("q0 abc\nqf\nq0 a q0 a left\nq0 _ qf _ stay\n");
+    tm.run(10).unwrap();
+    assert_eq!(tm.get_tape(), &['_', 'a', 'b', 'c']);
+
+    tm.set_head(1).unwrap();
+    assert_eq!(tm.head_symbol(), 'b');
+    assert_eq!(tm.get_tape(), &['a', 'b', 'c']);
+
+    tm.reset_to_start();
+    assert_eq!(tm.head_symbol(), 'b');
+    assert_eq!(tm.initial_configuration().tape_cell, 1);
+
+    let err = tm.set_head(3).unwrap_err();
+    assert!(matches!(err, TmError::HeadOutOfBounds { cell: 3, len: 3 }), "{err:?}");
+}
+
+#[test]
+fn trimmed_tape_drops_blanks_on_both_ends() {
+    assert_eq!(load("q0 __1_0__\nqf\n").trimmed_tape(), &['1', '_', '0']);
+    assert_eq!(load("q0 10\nqf\n").trimmed_tape(), &['1', '0']);
+}
+
+#[test]
+fn trimmed_tape_of_an_all_blank_tape_is_empty() {
+    assert!(load("q0 ___\nqf\n").trimmed_tape().is_empty());
+    assert!(load("start q0\nqf\n").trimmed_tape().is_empty());
+}
+
+#[test]
+fn circular_tape_wraps_around_a_full_loop() {
+    let mut tm = load("q0 abc\nqf\nq0 a q0 A right\nq0 b q0 B right\nq0 c q0 C right\n");
+    tm.set_tape_mode(TapeMode::Circular);
+
+    for cell in [1, 2, 0] {
+        assert_eq!(tm.step(), StepOutcome::Applied);
+        assert_eq!(tm.get_tape_cell(), cell);
+    }
+    assert_eq!(tm.get_tape(), &['A', 'B', 'C']);
+
+    tm.move_head(Direction::Lhs);
+    assert_eq!(tm.get_tape_cell(), 2);
+    assert_eq!(tm.head_symbol(), 'C');
+    assert_eq!(tm.get_tape().len(), 3);
+}
+
+#[test]
+fn left_bounded_tape_runs_a_machine_that_stays_on_it() {
+    let mut tm = load("q0 1@1\nqf\nq0 1 q1 0 left\nq1 1 qf 0 stay\n");
+    tm.set_tape_mode(TapeMode::LeftBounded);
+
+    assert_eq!(tm.run(10).unwrap(), 2);
+    assert_eq!(tm.get_tape(), &['0', '0']);
+}
+
+#[test]
+fn left_bounded_tape_rejects_moving_off_the_start() {
+    let mut tm = load("q0 1\nqf\nq0 1 q0 0 left\n");
+    tm.set_tape_mode(TapeMode::LeftBounded);
+
+    assert_eq!(tm.step(), StepOutcome::LeftBoundViolation);
+    assert_eq!(tm.get_tape(), &['1']);
+    let err = tm.run(10).unwrap_err();
+    assert!(matches!(err, TmError::LeftBoundViolation { steps: 0, .. }), "{err:?}");
+}
+
+#[test]
+fn machine_with_an_empty_tape_generates_its_output() {
+    let mut tm = load("start q0\nqf\nq0 _ q1 1 right\nq1 _ q2 0 right\nq2 _ qf 1 stay\n");
+    assert_eq!(tm.head_symbol(), '_');
+
+    assert_eq!(tm.run(10).unwrap(), 3);
+    assert_eq!(tm.get_tape(), &['1', '0', '1']);
+    assert_eq!(tm.output(false), "101");
+}
+
+#[test]
+fn output_drops_or_keeps_interior_blanks() {
+    let tm = load("q0 __1_0_1__\nqf\n");
+
+    assert_eq!(tm.output(false), "101");
+    assert_eq!(tm.output(true), "1_0_1");
+    assert_eq!(load("q0 ___\nqf\n").output(true), "");
+}
+
+#[test]
+fn walking_left_then_right_across_the_start_keeps_offsets_consistent() {
+    let mut tm = load("q0 1\nqf\n");
+
+    for _ in 0..5 {
+        tm.move_head(Direction::Lhs);
+    }
+    tm.write('x');
+    assert_eq!(tm.get_head_offset(), -5);
+    assert_eq!(tm.get_tape_cell(), 0);
+
+    for _ in 0..10 {
+        tm.move_head(Direction::Rhs);
+    }
+    tm.write('y');
+    assert_eq!(tm.get_head_offset(), 5);
+    assert_eq!(tm.get_tape_cell(), 10);
+    assert_eq!(tm.render_tape(32), "x _ _ _ _ 1 _ _ _ _ [y]");
+    assert_eq!(tm.trimmed_tape(), &['x', '_', '_', '_', '_', '1', '_', '_', '_', '_', 'y']);
+
+    tm.move_head(Direction::Rhs);
+    assert_eq!(tm.render_tape(32), "x _ _ _ _ 1 _ _ _ _ y [_]");
+    assert_eq!(tm.trimmed_tape().len(), 11);
+    assert_eq!(tm.tape_slice(-5, 1), vec!['x']);
+}
+
+#[test]
+fn reloading_a_config_resets_the_head_offset() {
+    let mut tm = load("q0 1\nqf\n");
+    tm.move_head(Direction::Lhs);
+    assert_eq!(tm.get_head_offset(), -1);
+
+    tm.load_cfg_str("q0 1\nqf\n").unwrap();
+    assert_eq!(tm.get_head_offset(), 0);
+    assert_eq!(tm.get_tape(), &['1']);
+}
+
+#[test]
+fn moving_right_forever_hits_the_tape_limit() {
+    let mut tm = load(LOOP);
+    tm.set_max_tape_size(Some(10));
+
+    let err = tm.run(100).unwrap_err();
+    assert!(matches!(err, TmError::TapeLimitExceeded(10)), "{err:?}");
+    assert_eq!(tm.get_tape().len(), 10);
+    assert_eq!(tm.get_step_count(), 9);
+    assert_eq!(tm.step(), StepOutcome::TapeLimitExceeded);
+
+    tm.set_max_tape_size(None);
+    assert_eq!(tm.step(), StepOutcome::Applied);
+    assert_eq!(tm.get_tape().len(), 11);
+}
+
+#[test]
+fn head_symbol_reads_the_cell_under_the_head() {
+    let mut tm = load("q0 0@12\nqf\n");
+    assert_eq!(tm.head_symbol(), '1');
+
+    tm.move_head(Direction::Rhs);
+    assert_eq!(tm.head_symbol(), '2');
+    tm.move_head(Direction::Lhs);
+    tm.move_head(Direction::Lhs);
+    assert_eq!(tm.head_symbol(), '0');
+}
+
+#[test]
+fn head_symbol_past_the_end_reads_blank() {
+    assert_eq!(TuringMachine::new().head_symbol(), '_');
+
+    let mut tm = load("blank 0\nq0 1\nqf\n");
+    tm.restore(&Configuration {
+        state: "q0".to_string(),
+        tape: vec!['1'],
+        tape_cell: 3,
+        origin: 0,
+    });
+    assert_eq!(tm.head_symbol(), '0');
+    assert_eq!(tm.get_tape(), &['1']);
+
+    tm.write('x');
+    assert_eq!(tm.head_symbol(), 'x');
+    assert_eq!(tm.get_tape(), &['1', '0', '0', 'x']);
+}
+
+#[test]
+fn tape_slice_spans_the_origin() {
+    let mut tm = load("q0 abc\nqf\n");
+    tm.move_head(Direction::Lhs);
+    tm.write('z');
+
+    assert_eq!(tm.tape_slice(-1, 3), vec!['z', 'a', 'b']);
+    assert_eq!(tm.tape_slice(0, 3), vec!['a', 'b', 'c']);
+    assert!(tm.tape_slice(1, 0).is_empty());
+}
+
+#[test]
+fn tape_slice_reads_blanks_outside_the_tape() {
+    let tm = load("blank 0\nq0 1@2\nqf\n");
+
+    assert_eq!(tm.tape_slice(-3, 2), vec!['0', '0']);
+    assert_eq!(tm.tape_slice(1, 4), vec!['2', '0', '0', '0']);
+    assert_eq!(tm.tape_slice(-1, 4), vec!['0', '1', '2', '0']);
+    assert_eq!(tm.get_tape(), &['1', '2']);
+}